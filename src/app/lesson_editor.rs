@@ -0,0 +1,477 @@
+use crate::core::character::Character;
+use crate::core::enums::Key;
+use crate::core::layout::{KeyPosition, KeyboardLayout, Layer, LayoutKey};
+use crate::core::lesson::Lesson;
+use crate::core::weighting_strategy::{FocusKey, WeightingStrategy};
+
+const DEFAULT_LESSON_LENGTH: u32 = 80;
+const DEFAULT_WORD_LENGTH: u8 = 4;
+const LESSON_LENGTH_STEP: u32 = 10;
+const MAX_WORD_LENGTH: u8 = 20;
+const MAX_FOCUS_WEIGHT: u8 = 4;
+
+/// Where the lesson of a draft goes once it is saved.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum DraftOrigin {
+    /// Appended to the lessons.
+    New,
+    /// Inserted after the lesson at the index, whose keys it inherits.
+    DerivedFrom(usize),
+    /// Replaces the lesson at the index.
+    Edit(usize),
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum DraftField {
+    Name,
+    Keys,
+    LessonLength,
+    WordLength,
+    Weighting,
+}
+
+impl DraftField {
+    pub const ALL: [DraftField; 5] = [
+        DraftField::Name,
+        DraftField::Keys,
+        DraftField::LessonLength,
+        DraftField::WordLength,
+        DraftField::Weighting,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DraftField::Name => "Name",
+            DraftField::Keys => "Keys",
+            DraftField::LessonLength => "Length",
+            DraftField::WordLength => "Word length",
+            DraftField::Weighting => "Weighting",
+        }
+    }
+    fn index(&self) -> usize {
+        DraftField::ALL
+            .iter()
+            .position(|field| field == self)
+            .unwrap_or_default()
+    }
+    fn next(&self) -> DraftField {
+        DraftField::ALL[(self.index() + 1) % DraftField::ALL.len()]
+    }
+    fn previous(&self) -> DraftField {
+        DraftField::ALL[(self.index() + DraftField::ALL.len() - 1) % DraftField::ALL.len()]
+    }
+}
+
+/// The form of the lesson editor. Keys are picked by typing them or from
+/// the rendered layout; the keys a lesson inherits from the one it is
+/// derived from cannot be removed.
+#[derive(PartialEq, Debug, Clone)]
+pub struct LessonDraft {
+    pub origin: DraftOrigin,
+    pub name: String,
+    pub inherited_keys: Vec<Character>,
+    /// The keys the lesson introduces.
+    pub keys: Vec<Character>,
+    pub lesson_length: u32,
+    pub word_length: u8,
+    /// How much more often the introduced keys are drawn, None for equal
+    /// weights.
+    pub focus_weight: Option<u8>,
+    /// The weighting of the edited lesson, kept as long as neither the
+    /// keys nor the weight are changed.
+    pub kept_weighting: Option<WeightingStrategy>,
+    /// Longest word of the word length distribution of the lesson, which
+    /// replaces the word length field.
+    pub distribution_max_length: Option<u8>,
+    pub field: DraftField,
+    /// Index of the key under the cursor, in reading order of the layout.
+    pub cursor: usize,
+    /// The layer of the layout keys are picked from.
+    pub layer: Layer,
+}
+
+impl LessonDraft {
+    pub fn new() -> LessonDraft {
+        LessonDraft {
+            origin: DraftOrigin::New,
+            name: String::new(),
+            inherited_keys: Vec::new(),
+            keys: Vec::new(),
+            lesson_length: DEFAULT_LESSON_LENGTH,
+            word_length: DEFAULT_WORD_LENGTH,
+            focus_weight: None,
+            kept_weighting: None,
+            distribution_max_length: None,
+            field: DraftField::Name,
+            cursor: 0,
+            layer: Layer::Base,
+        }
+    }
+
+    /// A lesson adding keys to the lesson at `index`, like `add_chars`.
+    pub fn derived_from(index: usize, parent: &Lesson) -> LessonDraft {
+        LessonDraft {
+            origin: DraftOrigin::DerivedFrom(index),
+            inherited_keys: parent.keys().to_vec(),
+            lesson_length: parent.lesson_length(),
+            word_length: parent.word_length(),
+            distribution_max_length: parent.word_lengths().map(|lengths| lengths.max_length()),
+            ..LessonDraft::new()
+        }
+    }
+
+    /// Edits the lesson at `index`.
+    pub fn edit(index: usize, lesson: &Lesson) -> LessonDraft {
+        let inherited_count = lesson.keys().len() - lesson.new_keys().len();
+        let focus_weight = match lesson.weighting_strategy() {
+            WeightingStrategy::FocusKey(focus_key) => {
+                Some((focus_key.weight.round() as u8).clamp(2, MAX_FOCUS_WEIGHT))
+            }
+            WeightingStrategy::EqualWeight => None,
+        };
+        LessonDraft {
+            origin: DraftOrigin::Edit(index),
+            name: lesson.name().to_string(),
+            inherited_keys: lesson.keys()[..inherited_count].to_vec(),
+            keys: lesson.new_keys().to_vec(),
+            lesson_length: lesson.lesson_length(),
+            word_length: lesson.word_length(),
+            focus_weight,
+            kept_weighting: Some(lesson.weighting_strategy().clone()),
+            distribution_max_length: lesson.word_lengths().map(|lengths| lengths.max_length()),
+            ..LessonDraft::new()
+        }
+    }
+
+    pub fn is_inherited(&self, grapheme: &str) -> bool {
+        self.inherited_keys.iter().any(|key| key.value == grapheme)
+    }
+    pub fn is_picked(&self, grapheme: &str) -> bool {
+        self.keys.iter().any(|key| key.value == grapheme)
+    }
+
+    /// Adds the key to the introduced keys or removes it again.
+    pub fn toggle_key(&mut self, character: Character) {
+        if self.is_inherited(&character.value) {
+            return;
+        }
+        match self
+            .keys
+            .iter()
+            .position(|key| key.value == character.value)
+        {
+            Some(index) => {
+                self.keys.remove(index);
+            }
+            None => self.keys.push(character),
+        }
+        self.kept_weighting = None;
+    }
+
+    /// Position of the key under the cursor on `layout`.
+    pub fn cursor_position(&self, layout: &KeyboardLayout) -> Option<KeyPosition> {
+        layout_keys(layout).get(self.cursor).map(|key| key.position)
+    }
+
+    /// Edits the selected field. Saving and cancelling are left to the
+    /// caller.
+    pub fn handle_key(&mut self, key: Key, layout: Option<&KeyboardLayout>) {
+        match (self.field, key) {
+            (_, Key::Tab) | (_, Key::Down) => self.field = self.field.next(),
+            (_, Key::Up) => self.field = self.field.previous(),
+            (DraftField::Name, Key::Char(c)) => self.name.push(c),
+            (DraftField::Name, Key::Backspace) => {
+                self.name.pop();
+            }
+            (DraftField::Keys, Key::Left) => self.cursor = self.cursor.saturating_sub(1),
+            (DraftField::Keys, Key::Right) => {
+                let key_count = layout.map_or(0, |layout| layout_keys(layout).len());
+                self.cursor = (self.cursor + 1).min(key_count.saturating_sub(1));
+            }
+            (DraftField::Keys, Key::Ctrl('l')) => {
+                let index = Layer::ALL
+                    .iter()
+                    .position(|layer| *layer == self.layer)
+                    .unwrap_or_default();
+                self.layer = Layer::ALL[(index + 1) % Layer::ALL.len()];
+            }
+            (DraftField::Keys, Key::Enter) => {
+                let character = layout
+                    .and_then(|layout| layout_keys(layout).get(self.cursor)?.character(self.layer));
+                if let Some(character) = character {
+                    self.toggle_key(Character::on_layer(character, self.layer));
+                }
+            }
+            (DraftField::Keys, Key::Backspace) if self.keys.pop().is_some() => {
+                self.kept_weighting = None;
+            }
+            // words are separated by spaces, so they cannot be a key
+            (DraftField::Keys, Key::Char(c)) if !c.is_whitespace() => {
+                let layer = layout.map_or(Layer::Base, |layout| layout.layer_of(&c.to_string()));
+                self.toggle_key(Character::on_layer(c, layer));
+            }
+            (DraftField::LessonLength, Key::Left) => {
+                self.lesson_length = self
+                    .lesson_length
+                    .saturating_sub(LESSON_LENGTH_STEP)
+                    .max(LESSON_LENGTH_STEP);
+            }
+            (DraftField::LessonLength, Key::Right) => self.lesson_length += LESSON_LENGTH_STEP,
+            // the distribution of the save file is not editable here
+            (DraftField::WordLength, _) if self.distribution_max_length.is_some() => {}
+            (DraftField::WordLength, Key::Left) => {
+                self.word_length = self.word_length.saturating_sub(1).max(1)
+            }
+            (DraftField::WordLength, Key::Right) => {
+                self.word_length = (self.word_length + 1).min(MAX_WORD_LENGTH)
+            }
+            (DraftField::Weighting, Key::Left) => {
+                self.kept_weighting = None;
+                self.focus_weight = match self.focus_weight {
+                    Some(weight) if weight > 2 => Some(weight - 1),
+                    _ => None,
+                }
+            }
+            (DraftField::Weighting, Key::Right) => {
+                self.kept_weighting = None;
+                self.focus_weight = match self.focus_weight {
+                    Some(weight) => Some((weight + 1).min(MAX_FOCUS_WEIGHT)),
+                    None => Some(2),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn weighting_strategy(&self) -> WeightingStrategy {
+        if let Some(weighting_strategy) = &self.kept_weighting {
+            return weighting_strategy.clone();
+        }
+        match self.focus_weight {
+            Some(weight) => WeightingStrategy::FocusKey(FocusKey {
+                focused_keys: self.keys.clone(),
+                weight: weight as f64,
+            }),
+            None => WeightingStrategy::EqualWeight,
+        }
+    }
+
+    pub fn describe_weighting(&self) -> String {
+        match self.focus_weight {
+            Some(weight) => format!("new keys {}x as often", weight),
+            None => String::from("equal"),
+        }
+    }
+
+    /// The lesson described by the draft. Derived and edited lessons keep
+    /// the settings of the lesson they are based on.
+    pub fn build(&self, lessons: &[Lesson]) -> Result<Lesson, &'static str> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err("The lesson needs a name");
+        }
+        if self.inherited_keys.is_empty() && self.keys.is_empty() {
+            return Err("The lesson needs at least one key");
+        }
+        let weighting_strategy = self.weighting_strategy();
+        let lesson = match self.origin {
+            DraftOrigin::New => Lesson::from_chars(
+                String::new(),
+                &[],
+                self.lesson_length,
+                self.word_length,
+                WeightingStrategy::EqualWeight,
+            )
+            .add_characters(name, &self.keys, weighting_strategy),
+            DraftOrigin::DerivedFrom(index) => lessons
+                .get(index)
+                .ok_or("The lesson to derive from no longer exists")?
+                .add_characters(name, &self.keys, weighting_strategy),
+            DraftOrigin::Edit(index) => lessons
+                .get(index)
+                .ok_or("The edited lesson no longer exists")?
+                .with_new_keys(
+                    name,
+                    self.inherited_keys.len(),
+                    &self.keys,
+                    weighting_strategy,
+                ),
+        };
+        Ok(lesson
+            .with_lesson_length(self.lesson_length)
+            .with_word_length(self.word_length))
+    }
+}
+
+impl Default for LessonDraft {
+    fn default() -> Self {
+        LessonDraft::new()
+    }
+}
+
+/// The keys of the layout row by row, the order the cursor moves in.
+fn layout_keys(layout: &KeyboardLayout) -> Vec<&LayoutKey> {
+    (0..layout.row_count() as u8)
+        .flat_map(|row| layout.row(row))
+        .collect()
+}
+
+#[cfg(test)]
+mod test_lesson_editor {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::core::word_length::WordLengthDistribution;
+
+    fn lessons() -> Vec<Lesson> {
+        let lesson_1 = Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b'],
+            40,
+            3,
+            WeightingStrategy::EqualWeight,
+        );
+        let lesson_2 = lesson_1.add_chars(
+            String::from("Lesson 2"),
+            &['c'],
+            WeightingStrategy::EqualWeight,
+        );
+        vec![lesson_1, lesson_2]
+    }
+
+    fn type_text(draft: &mut LessonDraft, text: &str) {
+        for c in text.chars() {
+            draft.handle_key(Key::Char(c), None);
+        }
+    }
+
+    #[test]
+    fn derived_lesson_inherits_the_keys_of_its_parent() {
+        let lessons = lessons();
+        let mut draft = LessonDraft::derived_from(1, &lessons[1]);
+        type_text(&mut draft, "Lesson 3");
+        draft.handle_key(Key::Tab, None);
+        type_text(&mut draft, "dxa x");
+        draft.handle_key(Key::Tab, None);
+        draft.handle_key(Key::Left, None);
+
+        let expected = lessons[1]
+            .add_chars(
+                String::from("Lesson 3"),
+                &['d'],
+                WeightingStrategy::EqualWeight,
+            )
+            .with_lesson_length(30);
+        assert_eq!(draft.build(&lessons), Ok(expected));
+        // "a" is inherited, typing "x" again removes it
+        assert_eq!(draft.keys, vec![Character::new('d')]);
+    }
+
+    #[test]
+    fn editing_keeps_the_inherited_keys() {
+        let lessons = lessons();
+        let mut draft = LessonDraft::edit(1, &lessons[1]);
+        assert_eq!(draft.inherited_keys.len(), 2);
+        draft.field = DraftField::Keys;
+        type_text(&mut draft, "ce");
+        draft.field = DraftField::Weighting;
+        draft.handle_key(Key::Right, None);
+        draft.handle_key(Key::Right, None);
+
+        let lesson = draft.build(&lessons).unwrap();
+        assert_eq!(lesson.name(), "Lesson 2");
+        assert_eq!(lesson.new_keys(), &[Character::new('e')]);
+        assert_eq!(
+            lesson.weighting_strategy(),
+            &WeightingStrategy::FocusKey(FocusKey {
+                focused_keys: vec![Character::new('e')],
+                weight: 3.0
+            })
+        );
+        assert_eq!(LessonDraft::edit(1, &lesson).focus_weight, Some(3))
+    }
+
+    #[test]
+    fn editing_keeps_the_weighting_unless_keys_or_weight_change() {
+        let focus_key = WeightingStrategy::FocusKey(FocusKey {
+            focused_keys: vec![Character::new('a')],
+            weight: 6.0,
+        });
+        let lessons = vec![lessons()[1]
+            .add_chars(String::from("Lesson 3"), &['d'], focus_key.clone())
+            .with_word_lengths(WordLengthDistribution::Uniform { min: 2, max: 5 })];
+        let mut draft = LessonDraft::edit(0, &lessons[0]);
+        type_text(&mut draft, " renamed");
+        draft.field = DraftField::WordLength;
+        draft.handle_key(Key::Right, None);
+        let lesson = draft.build(&lessons).unwrap();
+        assert_eq!(lesson.weighting_strategy(), &focus_key);
+        assert_eq!(lesson.word_length(), 3);
+
+        draft.field = DraftField::Keys;
+        type_text(&mut draft, "e");
+        assert_eq!(
+            draft.build(&lessons).unwrap().weighting_strategy(),
+            &WeightingStrategy::FocusKey(FocusKey {
+                focused_keys: vec![Character::new('d'), Character::new('e')],
+                weight: 4.0
+            })
+        );
+    }
+
+    #[test]
+    fn built_lesson_draws_new_keys_by_the_chosen_weighting() {
+        let lessons = lessons();
+        let mut draft = LessonDraft::derived_from(1, &lessons[1]);
+        type_text(&mut draft, "Lesson 3");
+        draft.field = DraftField::Keys;
+        type_text(&mut draft, "d");
+        draft.field = DraftField::LessonLength;
+        for _ in 0..10 {
+            draft.handle_key(Key::Right, None);
+        }
+        draft.field = DraftField::Weighting;
+        for _ in 0..3 {
+            draft.handle_key(Key::Right, None);
+        }
+        assert_eq!(draft.describe_weighting(), "new keys 4x as often");
+
+        let content = draft
+            .build(&lessons)
+            .unwrap()
+            .generate_lesson_content_with_seed(7);
+        let a_count = content.matches('a').count();
+        let d_count = content.matches('d').count();
+        assert!(d_count > 2 * a_count)
+    }
+
+    #[test]
+    fn keys_are_picked_from_the_layout() {
+        let bone = KeyboardLayout::bone();
+        let mut draft = LessonDraft {
+            field: DraftField::Keys,
+            ..LessonDraft::new()
+        };
+        draft.cursor = layout_keys(&bone)
+            .iter()
+            .position(|key| key.character(Layer::Base) == Some('c'))
+            .unwrap();
+        draft.handle_key(Key::Enter, Some(&bone));
+        draft.handle_key(Key::Ctrl('l'), Some(&bone));
+        draft.handle_key(Key::Enter, Some(&bone));
+        draft.handle_key(Key::Right, Some(&bone));
+        draft.handle_key(Key::Char('T'), Some(&bone));
+        assert_eq!(
+            draft.keys,
+            vec![
+                Character::new('c'),
+                Character::on_layer('C', Layer::Shift),
+                Character::on_layer('T', Layer::Shift)
+            ]
+        );
+        assert_eq!(draft.build(&[]), Err("The lesson needs a name"));
+        draft.keys.clear();
+        draft.name = String::from("Empty");
+        assert_eq!(draft.build(&[]), Err("The lesson needs at least one key"))
+    }
+}
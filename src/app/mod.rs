@@ -1,3 +1,8 @@
+pub mod lesson_editor;
+pub mod persistence;
+pub mod practice_planner;
+pub mod replay;
 pub mod selectable_session_list;
+pub mod session_summary;
 pub mod trainer;
 pub mod training_session;
@@ -0,0 +1,121 @@
+use std::fs::OpenOptions;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::app::selectable_session_list::SelectableLessonList;
+
+/// Reads the lessons, records and settings of a save file.
+pub fn load_lesson_list(file_path: impl AsRef<Path>) -> anyhow::Result<SelectableLessonList> {
+    let file_path = file_path.as_ref();
+    let file = OpenOptions::new()
+        .read(true)
+        .open(file_path)
+        .with_context(|| format!("could not open {}", file_path.display()))?;
+    let mut lesson_list: SelectableLessonList = serde_json::from_reader(file)
+        .with_context(|| format!("{} is not a valid save file", file_path.display()))?;
+    lesson_list.infer_new_keys();
+    Ok(lesson_list)
+}
+
+/// Writes the save file, replacing an existing one.
+pub fn save_lesson_list(
+    lesson_list: &SelectableLessonList,
+    file_path: impl AsRef<Path>,
+) -> anyhow::Result<()> {
+    let file_path = file_path.as_ref();
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(file_path)
+        .with_context(|| format!("could not write {}", file_path.display()))?;
+    serde_json::to_writer(file, lesson_list)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_persistence {
+    use pretty_assertions::assert_eq;
+
+    use crate::core::lesson::Lesson;
+    use crate::core::stats::record;
+    use crate::core::weighting_strategy::WeightingStrategy;
+
+    use super::*;
+
+    #[test]
+    fn saved_lesson_list_is_loaded_again() {
+        let lesson = Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b'],
+            10,
+            4,
+            WeightingStrategy::EqualWeight,
+        );
+        let mut lesson_list = SelectableLessonList::new(vec![lesson]);
+        lesson_list.select_lesson(0);
+        lesson_list.add_record_to_current_session(record(30, 1));
+        let file_path = std::env::temp_dir().join(format!("lessons-{}.json", std::process::id()));
+        let file_path = file_path.to_str().unwrap();
+
+        save_lesson_list(&lesson_list, file_path).unwrap();
+        let loaded = load_lesson_list(file_path).unwrap();
+        std::fs::remove_file(file_path).unwrap();
+
+        assert_eq!(loaded.lessons(), lesson_list.lessons());
+        assert_eq!(loaded.selected_index(), Some(0));
+        assert_eq!(loaded.personal_best(0), Some(30))
+    }
+    #[test]
+    fn missing_save_file_is_an_error() {
+        assert!(load_lesson_list("does/not/exist.json").is_err())
+    }
+    #[test]
+    fn invalid_save_file_is_named_in_the_error() {
+        let file_path = std::env::temp_dir().join(format!("invalid-{}.json", std::process::id()));
+        let file_path = file_path.to_str().unwrap();
+        std::fs::write(file_path, "{\"lessons\": 3}").unwrap();
+
+        let error = load_lesson_list(file_path).err();
+        std::fs::remove_file(file_path).unwrap();
+
+        assert_eq!(
+            error.map(|error| error.to_string()).unwrap_or_default(),
+            format!("{} is not a valid save file", file_path)
+        )
+    }
+    #[test]
+    fn new_keys_of_old_save_files_are_inferred_on_load() {
+        let file_path = std::env::temp_dir().join(format!("old-{}.json", std::process::id()));
+        let file_path = file_path.to_str().unwrap();
+        let lesson = |name: &str, keys: &str| {
+            let keys: Vec<String> = keys
+                .chars()
+                .map(|key| format!("{{\"value\": \"{}\"}}", key))
+                .collect();
+            format!(
+                "{{\"name\": \"{}\", \"keys\": [{}], \"weighting_strategy\": \"EqualWeight\", \
+                 \"lesson_length\": 10, \"word_length\": 2}}",
+                name,
+                keys.join(", ")
+            )
+        };
+        let save_file = format!(
+            "{{\"lessons\": [{}, {}], \"selected_index\": null, \"training_records\": {{}}}}",
+            lesson("Lesson 1", "ab"),
+            lesson("Lesson 2", "abc")
+        );
+        std::fs::write(file_path, save_file).unwrap();
+
+        let loaded = load_lesson_list(file_path).unwrap();
+        std::fs::remove_file(file_path).unwrap();
+
+        let new_keys: Vec<usize> = loaded
+            .lessons()
+            .iter()
+            .map(|lesson| lesson.new_keys().len())
+            .collect();
+        assert_eq!(new_keys, vec![2, 1])
+    }
+}
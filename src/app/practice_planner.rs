@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::app::selectable_session_list::SelectableLessonList;
+use crate::core::character::Character;
+use crate::core::lesson::Lesson;
+use crate::core::mastery::LessonState;
+use crate::core::scheduling::ReviewState;
+
+const MAX_REVIEWS_PER_DAY: usize = 3;
+const MAX_WEAK_KEYS: usize = 3;
+const MIN_WEAK_KEY_ERRORS: u16 = 3;
+const WEAK_KEY_WEIGHT: f64 = 4.0;
+const WEAK_KEY_DAYS: i64 = 7;
+
+pub enum PlannedSession {
+    Review {
+        lesson_index: usize,
+        due: DateTime<Utc>,
+    },
+    NewLesson {
+        lesson_index: usize,
+    },
+    /// Not part of the lesson list, its sessions are recorded as drills.
+    WeakKeyDrill {
+        lesson: Box<Lesson>,
+    },
+}
+
+/// Suggests today's practice: lessons due for review, most overdue first,
+/// a drill of the keys missed most during the last week and the next lesson
+/// that was never practiced.
+pub fn plan_practice(
+    lesson_list: &SelectableLessonList,
+    now: DateTime<Utc>,
+) -> Vec<PlannedSession> {
+    let mut reviews: Vec<(usize, DateTime<Utc>)> = (0..lesson_list.lessons().len())
+        .filter_map(|index| {
+            let criteria = lesson_list.mastery_criteria_for(index);
+            let review_state =
+                ReviewState::from_records(lesson_list.lesson_records(index), criteria)?;
+            if review_state.is_due(now) {
+                Some((index, review_state.due))
+            } else {
+                None
+            }
+        })
+        .collect();
+    reviews.sort_by_key(|(_, due)| *due);
+
+    let mut plan: Vec<PlannedSession> = reviews
+        .into_iter()
+        .take(MAX_REVIEWS_PER_DAY)
+        .map(|(lesson_index, due)| PlannedSession::Review { lesson_index, due })
+        .collect();
+    if let Some(lesson) = weak_key_drill(lesson_list, now) {
+        plan.push(PlannedSession::WeakKeyDrill {
+            lesson: Box::new(lesson),
+        });
+    }
+    let new_lesson = (0..lesson_list.lessons().len()).find(|index| {
+        lesson_list.lesson_records(*index).is_empty()
+            && lesson_list.lesson_state(*index) != LessonState::Locked
+    });
+    if let Some(lesson_index) = new_lesson {
+        plan.push(PlannedSession::NewLesson { lesson_index });
+    }
+    plan
+}
+
+/// Keys with the most errors in all sessions of the last week.
+pub fn weak_keys(lesson_list: &SelectableLessonList, now: DateTime<Utc>) -> Vec<String> {
+    let since = now - Duration::days(WEAK_KEY_DAYS);
+    let mut errors_by_key: BTreeMap<&str, u16> = BTreeMap::new();
+    let records = (0..lesson_list.lessons().len())
+        .flat_map(|index| lesson_list.lesson_records(index))
+        .chain(lesson_list.drill_records());
+    for record in records {
+        if record.timestamp < since {
+            continue;
+        }
+        for (key, errors) in record.stats.errors.errors_by_key.iter() {
+            *errors_by_key.entry(key.as_str()).or_default() += errors;
+        }
+    }
+    let mut weak_keys: Vec<(&str, u16)> = errors_by_key
+        .into_iter()
+        .filter(|(key, errors)| *errors >= MIN_WEAK_KEY_ERRORS && key.trim() != "")
+        .collect();
+    weak_keys.sort_by_key(|(_, errors)| std::cmp::Reverse(*errors));
+    weak_keys
+        .into_iter()
+        .take(MAX_WEAK_KEYS)
+        .map(|(key, _)| key.to_string())
+        .collect()
+}
+
+/// Drills the weak keys within the lesson that was practiced last.
+pub fn weak_key_drill(lesson_list: &SelectableLessonList, now: DateTime<Utc>) -> Option<Lesson> {
+    let last_practiced = (0..lesson_list.lessons().len())
+        .filter_map(|index| {
+            let last_record = lesson_list.lesson_records(index).last()?;
+            Some((index, last_record.timestamp))
+        })
+        .max_by_key(|(_, timestamp)| *timestamp)?
+        .0;
+    let base_lesson = &lesson_list.lessons()[last_practiced];
+    let focused_keys: Vec<Character> = weak_keys(lesson_list, now)
+        .iter()
+        .filter_map(|key| {
+            base_lesson
+                .keys()
+                .iter()
+                .find(|character| &character.value == key)
+                .cloned()
+        })
+        .collect();
+    if focused_keys.is_empty() {
+        return None;
+    }
+    let name = format!(
+        "Weak keys: {}",
+        focused_keys
+            .iter()
+            .map(|key| key.value.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    );
+    Some(base_lesson.focus_on(name, focused_keys, WEAK_KEY_WEIGHT))
+}
+
+#[cfg(test)]
+mod test_practice_planner {
+    use pretty_assertions::assert_eq;
+
+    use crate::core::stats::record;
+    use crate::core::stats::TrainingRecord;
+    use crate::core::weighting_strategy::WeightingStrategy;
+    use crate::wrapper::fake_clock::FakeClock;
+
+    use super::*;
+
+    fn lesson_list() -> SelectableLessonList {
+        let lesson_1 = Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b'],
+            10,
+            4,
+            WeightingStrategy::EqualWeight,
+        );
+        let lesson_2 = lesson_1.add_chars(
+            String::from("Lesson 2"),
+            &['c'],
+            WeightingStrategy::EqualWeight,
+        );
+        SelectableLessonList::new(vec![lesson_1, lesson_2])
+    }
+
+    fn record_now(missed_key: &str, errors: u16) -> TrainingRecord {
+        let mut record = record(30, errors);
+        record.timestamp = FakeClock::now();
+        record
+            .stats
+            .errors
+            .errors_by_key
+            .insert(missed_key.to_string(), errors);
+        record
+    }
+
+    fn describe(plan: &[PlannedSession]) -> Vec<String> {
+        plan.iter()
+            .map(|session| match session {
+                PlannedSession::Review { lesson_index, .. } => format!("review {}", lesson_index),
+                PlannedSession::NewLesson { lesson_index } => format!("new {}", lesson_index),
+                PlannedSession::WeakKeyDrill { lesson } => lesson.name().to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn suggests_first_lesson_without_records() {
+        let plan = plan_practice(&lesson_list(), FakeClock::now());
+        assert_eq!(describe(&plan), vec!["new 0"])
+    }
+    #[test]
+    fn practiced_lesson_is_reviewed_once_due() {
+        let mut list = lesson_list();
+        list.select_next_lesson();
+        list.add_record_to_current_session(record_now("a", 0));
+        assert_eq!(
+            describe(&plan_practice(&list, FakeClock::now())),
+            vec!["new 1"]
+        );
+        FakeClock::advance(Duration::days(1));
+        assert_eq!(
+            describe(&plan_practice(&list, FakeClock::now())),
+            vec!["review 0", "new 1"]
+        )
+    }
+    #[test]
+    fn weak_keys_of_last_week_are_drilled() {
+        let mut list = lesson_list();
+        list.select_next_lesson();
+        list.add_record_to_current_session(record_now("b", 5));
+        let plan = plan_practice(&list, FakeClock::now());
+        assert_eq!(describe(&plan), vec!["Weak keys: b", "new 1"]);
+        FakeClock::advance(Duration::days(8));
+        let plan = plan_practice(&list, FakeClock::now());
+        assert_eq!(describe(&plan), vec!["review 0", "new 1"])
+    }
+    #[test]
+    fn few_errors_do_not_make_a_key_weak() {
+        let mut list = lesson_list();
+        list.select_next_lesson();
+        list.add_record_to_current_session(record_now("b", 2));
+        assert_eq!(weak_keys(&list, FakeClock::now()), Vec::<String>::new())
+    }
+}
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::app::training_session::TrainingSession;
+use crate::core::enums::Key;
+use crate::core::input::{InputEvent, InputSource};
+use crate::core::stats::{Keystroke, Recording};
+
+/// Gaps between keystrokes from this long on are shown as hesitations.
+const HESITATION_MILLISECONDS: i64 = 1000;
+const MAX_SPEED: u32 = 16;
+
+/// Plays a recorded session back through a `TrainingSession`, so it shows
+/// the same progress and errors as when it was typed. Keys are fed with
+/// their recorded timing, so the finished replay has the statistics of the
+/// original session whatever the playback speed.
+pub struct Replay {
+    session: TrainingSession,
+    keystrokes: Vec<Keystroke>,
+    next_keystroke: usize,
+    speed: u32,
+    /// The time of the recording played so far.
+    position: Duration,
+    last_update: DateTime<Utc>,
+    /// The time the first keystroke is replayed at.
+    origin: DateTime<Utc>,
+}
+
+impl Replay {
+    /// Plays `recording` back into `session`, which has to be created for
+    /// the recorded content. It starts at `now` with real speed.
+    pub fn new(recording: &Recording, session: TrainingSession, now: DateTime<Utc>) -> Replay {
+        let time_limit = recording
+            .time_limit_seconds
+            .map(|seconds| Duration::seconds(seconds as i64));
+        Replay {
+            // the recorded offsets leave pauses out already
+            session: session
+                .with_idle_timeout(None)
+                .with_time_limit(time_limit)
+                .with_ghost(None),
+            keystrokes: recording.keystrokes.clone(),
+            next_keystroke: 0,
+            speed: 1,
+            position: Duration::zero(),
+            last_update: now,
+            origin: now,
+        }
+    }
+
+    /// Moves the playback on to `now` and feeds the keys typed up to there.
+    pub fn advance_to(&mut self, now: DateTime<Utc>) {
+        self.position = self.position + (now - self.last_update) * self.speed as i32;
+        self.last_update = now;
+        while let Some(keystroke) = self.keystrokes.get(self.next_keystroke) {
+            let offset = Duration::milliseconds(keystroke.offset_milliseconds as i64);
+            if offset > self.position {
+                break;
+            }
+            let event = InputEvent::new(Key::Char(keystroke.key), self.origin + offset)
+                .with_source(InputSource::Replay);
+            self.session.handle_event(&event);
+            self.next_keystroke += 1;
+        }
+    }
+
+    pub fn session(&self) -> &TrainingSession {
+        &self.session
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_keystroke == self.keystrokes.len()
+    }
+
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * 2).min(MAX_SPEED);
+    }
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / 2).max(1);
+    }
+
+    /// Time of the recording since the last key while the next one has not
+    /// been typed for a noticeable time.
+    pub fn hesitation(&self) -> Option<Duration> {
+        if self.is_finished() || self.next_keystroke == 0 {
+            return None;
+        }
+        let last_keystroke = self.keystrokes[self.next_keystroke - 1];
+        let gap = self.position - Duration::milliseconds(last_keystroke.offset_milliseconds as i64);
+        if gap >= Duration::milliseconds(HESITATION_MILLISECONDS) {
+            Some(gap)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_replay {
+    use pretty_assertions::assert_eq;
+
+    use crate::wrapper::fake_clock::FakeClock;
+
+    use super::*;
+
+    /// Types `keys`, each after the given delay in milliseconds.
+    fn typed_session(content: &str, keys: &[(i64, char)]) -> TrainingSession {
+        let mut session = TrainingSession::new(String::from(content));
+        for (delay, key) in keys {
+            FakeClock::advance(Duration::milliseconds(*delay));
+            session.handle_key(*key);
+        }
+        session
+    }
+
+    #[test]
+    fn replay_reproduces_the_recorded_session() {
+        let original = typed_session(
+            "abc d",
+            &[
+                (0, 'a'),
+                (300, 'x'),
+                (100, 'b'),
+                (2000, 'c'),
+                (200, ' '),
+                (150, 'd'),
+            ],
+        );
+        let recording = original.recording();
+
+        let mut unit = Replay::new(
+            &recording,
+            TrainingSession::new(recording.content.clone()),
+            FakeClock::now(),
+        );
+        FakeClock::advance(Duration::seconds(10));
+        unit.advance_to(FakeClock::now());
+
+        assert!(unit.is_finished());
+        let (replayed, original) = (unit.session().stats(), original.stats());
+        assert_eq!(replayed.errors, original.errors);
+        assert_eq!(replayed.typed_characters, original.typed_characters);
+        assert_eq!(replayed.duration_seconds, original.duration_seconds);
+        assert_eq!(
+            unit.session().slowest_keys(),
+            vec![
+                (String::from("c"), Duration::milliseconds(2000)),
+                (String::from("b"), Duration::milliseconds(400)),
+                (String::from(" "), Duration::milliseconds(200)),
+                (String::from("d"), Duration::milliseconds(150))
+            ]
+        );
+        assert_eq!(unit.session().recording(), recording)
+    }
+    #[test]
+    fn keys_are_played_at_their_offsets_scaled_by_the_speed() {
+        let recording = typed_session("abc", &[(0, 'a'), (300, 'b'), (2000, 'c')]).recording();
+        let start = FakeClock::now();
+        let mut unit = Replay::new(
+            &recording,
+            TrainingSession::new(recording.content.clone()),
+            start,
+        );
+        unit.faster();
+        assert_eq!(unit.speed(), 2);
+
+        unit.advance_to(start + Duration::milliseconds(100));
+        assert_eq!(unit.session().current_char(), Some("b"));
+        unit.advance_to(start + Duration::milliseconds(150));
+        assert_eq!(unit.session().current_char(), Some("c"));
+        assert_eq!(unit.hesitation(), None);
+        unit.advance_to(start + Duration::milliseconds(1000));
+        assert_eq!(unit.hesitation(), Some(Duration::milliseconds(1700)));
+        unit.advance_to(start + Duration::milliseconds(1150));
+        assert!(unit.is_finished());
+        assert!(unit.session().is_finished())
+    }
+}
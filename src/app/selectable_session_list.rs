@@ -1,15 +1,64 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::core::achievements::{practice_streak, Achievement, PracticeStreak, Profile};
+use crate::core::ghost::Ghost;
+use crate::core::goals::{self, DailyGoal, DailyPractice};
+use crate::core::layout::KeyboardLayout;
 use crate::core::lesson::Lesson;
+use crate::core::mastery::{LessonState, MasteryCriteria};
+use crate::core::routine::{Routine, StepSource};
 use crate::core::stats::TrainingRecord;
+use crate::core::typing_speed::TypingSpeed;
 
 #[derive(Serialize, Deserialize)]
 pub struct SelectableLessonList {
     lessons: Vec<Lesson>,
     selected_index: Option<usize>,
     training_records: HashMap<usize, Vec<TrainingRecord>>,
+    /// Sessions of drills, which are not part of the lesson list.
+    #[serde(default)]
+    drill_records: Vec<TrainingRecord>,
+    #[serde(default)]
+    layout: Option<KeyboardLayout>,
+    #[serde(default)]
+    mastery_criteria: Option<MasteryCriteria>,
+    #[serde(default)]
+    auto_advance: bool,
+    #[serde(default)]
+    profile: Profile,
+    #[serde(default)]
+    daily_goal: Option<DailyGoal>,
+    /// Seconds without a keystroke after which a session pauses, 0 never
+    /// pauses.
+    #[serde(default = "default_idle_timeout_seconds")]
+    idle_timeout_seconds: u32,
+    /// Listed after the lessons, `selected_index` counts them as entries
+    /// following the last lesson.
+    #[serde(default)]
+    routines: Vec<Routine>,
+    #[serde(default)]
+    ghost: Option<Ghost>,
+}
+
+fn default_idle_timeout_seconds() -> u32 {
+    10
+}
+
+/// Number of sessions of a lesson, or of all drills, whose keystrokes are
+/// kept for replays. Older records only keep their statistics, so the save
+/// file does not grow with every keystroke ever typed.
+const KEPT_RECORDINGS: usize = 5;
+
+fn drop_old_recordings(records: &mut [TrainingRecord]) {
+    records
+        .iter_mut()
+        .rev()
+        .filter(|record| record.recording.is_some())
+        .skip(KEPT_RECORDINGS)
+        .for_each(|record| record.recording = None);
 }
 
 impl SelectableLessonList {
@@ -18,8 +67,60 @@ impl SelectableLessonList {
             lessons,
             selected_index: None,
             training_records: HashMap::new(),
+            drill_records: Vec::new(),
+            layout: None,
+            mastery_criteria: None,
+            auto_advance: false,
+            profile: Profile::default(),
+            daily_goal: None,
+            idle_timeout_seconds: default_idle_timeout_seconds(),
+            routines: Vec::new(),
+            ghost: None,
         }
     }
+    /// Criteria for all lessons of this course that do not define their own.
+    /// Without any criteria lessons are never locked.
+    pub fn with_mastery_criteria(mut self, mastery_criteria: MasteryCriteria) -> Self {
+        self.mastery_criteria = Some(mastery_criteria);
+        self
+    }
+    pub fn with_layout(mut self, layout: KeyboardLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+    pub fn with_daily_goal(mut self, daily_goal: DailyGoal) -> Self {
+        self.daily_goal = Some(daily_goal);
+        self
+    }
+    pub fn with_routines(mut self, routines: Vec<Routine>) -> Self {
+        self.routines = routines;
+        self
+    }
+    pub fn with_ghost(mut self, ghost: Ghost) -> Self {
+        self.ghost = Some(ghost);
+        self
+    }
+    pub fn with_idle_timeout_seconds(mut self, idle_timeout_seconds: u32) -> Self {
+        self.idle_timeout_seconds = idle_timeout_seconds;
+        self
+    }
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        match self.idle_timeout_seconds {
+            0 => None,
+            seconds => Some(Duration::seconds(seconds as i64)),
+        }
+    }
+    /// Fills in the new keys of lessons from older save files, each from
+    /// the lesson before it.
+    pub fn infer_new_keys(&mut self) {
+        for index in 0..self.lessons.len() {
+            let (previous, lessons) = self.lessons.split_at_mut(index);
+            lessons[0].infer_new_keys(previous.last());
+        }
+    }
+    pub fn layout(&self) -> Option<&KeyboardLayout> {
+        self.layout.as_ref()
+    }
     pub fn lessons(&self) -> &[Lesson] {
         &self.lessons[..]
     }
@@ -29,28 +130,110 @@ impl SelectableLessonList {
     pub fn current_lesson(&self) -> Option<&Lesson> {
         self.lessons.get(self.selected_index?)
     }
+    pub fn routines(&self) -> &[Routine] {
+        &self.routines[..]
+    }
+    pub fn current_routine(&self) -> Option<&Routine> {
+        let routine_index = self.selected_index?.checked_sub(self.lessons.len())?;
+        self.routines.get(routine_index)
+    }
+    /// Selects a lesson or routine by its position in the list.
+    pub fn select_lesson_entry(&mut self, entry_index: usize) {
+        if entry_index < self.entry_count() {
+            self.selected_index = Some(entry_index);
+        }
+    }
+    fn entry_count(&self) -> usize {
+        self.lessons.len() + self.routines.len()
+    }
 
     pub fn current_lesson_records(&self) -> &[TrainingRecord] {
-        //Option<&Vec<TrainingRecord>> {
-        let maybe_data = match self.selected_index {
-            None => None,
-            Some(index) => self.training_records.get(&index),
-        };
-        match maybe_data {
-            Some(a) => a.as_ref(),
+        match self.selected_index {
             None => &[] as &[TrainingRecord],
+            Some(index) => self.lesson_records(index),
+        }
+    }
+    pub fn lesson_records(&self, index: usize) -> &[TrainingRecord] {
+        match self.training_records.get(&index) {
+            Some(records) => records.as_ref(),
+            None => &[] as &[TrainingRecord],
+        }
+    }
+    pub fn mastery_criteria_for(&self, index: usize) -> Option<&MasteryCriteria> {
+        self.lessons
+            .get(index)?
+            .mastery_criteria()
+            .or(self.mastery_criteria.as_ref())
+    }
+    fn is_mastered(&self, index: usize) -> bool {
+        match self.mastery_criteria_for(index) {
+            Some(criteria) => criteria.is_met_by(self.lesson_records(index)),
+            None => false,
+        }
+    }
+    /// Whether the lesson lets the one after it be unlocked. Lessons
+    /// without criteria cannot be mastered, so they always do.
+    fn is_passed(&self, index: usize) -> bool {
+        match self.mastery_criteria_for(index) {
+            Some(criteria) => criteria.is_met_by(self.lesson_records(index)),
+            None => true,
+        }
+    }
+    /// A lesson is unlocked if it is the first one, has no criteria or the
+    /// lesson before it is mastered or has no criteria.
+    pub fn lesson_state(&self, index: usize) -> LessonState {
+        if self.mastery_criteria_for(index).is_none() {
+            LessonState::Unlocked
+        } else if self.is_mastered(index) {
+            LessonState::Mastered
+        } else if index == 0 || self.is_passed(index - 1) {
+            LessonState::Unlocked
+        } else {
+            LessonState::Locked
+        }
+    }
+    pub fn is_current_lesson_locked(&self) -> bool {
+        match self.selected_index {
+            Some(index) => self.lesson_state(index) == LessonState::Locked,
+            None => true,
+        }
+    }
+    pub fn auto_advance(&self) -> bool {
+        self.auto_advance
+    }
+    pub fn toggle_auto_advance(&mut self) {
+        self.auto_advance = !self.auto_advance;
+    }
+    /// Selects the next lesson if auto advance is enabled and the current
+    /// lesson has just been mastered.
+    pub fn advance_if_mastered(&mut self) -> bool {
+        match self.selected_index {
+            Some(index)
+                if self.auto_advance
+                    && self.lesson_state(index) == LessonState::Mastered
+                    && index + 1 < self.lessons.len() =>
+            {
+                self.selected_index = Some(index + 1);
+                true
+            }
+            _ => false,
+        }
+    }
+    pub fn select_lesson(&mut self, index: usize) {
+        if index < self.lessons.len() {
+            self.selected_index = Some(index);
         }
     }
     pub fn select_next_lesson(&mut self) {
         match self.selected_index {
             None => {
-                if !self.lessons.is_empty() {
+                if self.entry_count() > 0 {
                     self.selected_index = Some(0);
                 }
             }
             Some(current) => {
                 let next = current + 1;
-                if self.lessons.len() > next {
+                if self.entry_count() > next {
                     self.selected_index = Some(next);
                 }
             }
@@ -59,8 +242,8 @@ impl SelectableLessonList {
     pub fn select_prev_lesson(&mut self) {
         match self.selected_index {
             None => {
-                if !self.lessons.is_empty() {
-                    self.selected_index = Some(self.lessons.len() - 1);
+                if self.entry_count() > 0 {
+                    self.selected_index = Some(self.entry_count() - 1);
                 }
             }
             Some(0) => {}
@@ -70,18 +253,203 @@ impl SelectableLessonList {
             }
         }
     }
-    pub fn add_record_to_current_session(&mut self, trainig_record: TrainingRecord) {
-        let entry = self
+    /// Inserts a lesson at `index`, the lessons from there on move down
+    /// together with their records.
+    pub fn insert_lesson(&mut self, index: usize, lesson: Lesson) {
+        let index = index.min(self.lessons.len());
+        self.lessons.insert(index, lesson);
+        self.move_lesson_references(self.lessons.len() - 1, |old_index| {
+            Some(if old_index < index {
+                old_index
+            } else {
+                old_index + 1
+            })
+        });
+    }
+    /// Replaces a lesson, e.g. after editing it. Its records are kept.
+    pub fn replace_lesson(&mut self, index: usize, lesson: Lesson) {
+        if let Some(current) = self.lessons.get_mut(index) {
+            *current = lesson;
+        }
+    }
+    /// Removes a lesson with its records and the routine steps typing it.
+    pub fn remove_lesson(&mut self, index: usize) -> Option<Lesson> {
+        if index >= self.lessons.len() {
+            return None;
+        }
+        let lesson = self.lessons.remove(index);
+        self.move_lesson_references(self.lessons.len() + 1, |old_index| {
+            match old_index.cmp(&index) {
+                std::cmp::Ordering::Less => Some(old_index),
+                std::cmp::Ordering::Equal => None,
+                std::cmp::Ordering::Greater => Some(old_index - 1),
+            }
+        });
+        Some(lesson)
+    }
+    /// Swaps two lessons, their records and selection go along with them.
+    pub fn swap_lessons(&mut self, first: usize, second: usize) {
+        if first >= self.lessons.len() || second >= self.lessons.len() {
+            return;
+        }
+        self.lessons.swap(first, second);
+        self.move_lesson_references(self.lessons.len(), |old_index| {
+            Some(if old_index == first {
+                second
+            } else if old_index == second {
+                first
+            } else {
+                old_index
+            })
+        });
+    }
+    /// Updates everything referring to lessons by index after the lessons
+    /// changed. `new_index` maps the index of a lesson out of the
+    /// `old_lesson_count` before to its index now, None if it was removed.
+    fn move_lesson_references(
+        &mut self,
+        old_lesson_count: usize,
+        new_index: impl Fn(usize) -> Option<usize>,
+    ) {
+        self.training_records = self
             .training_records
-            .entry(self.selected_index.unwrap())
-            .or_insert(Vec::new());
-        entry.push(trainig_record);
+            .drain()
+            .filter_map(|(index, records)| Some((new_index(index)?, records)))
+            .collect();
+        for routine in self.routines.iter_mut() {
+            routine.steps.retain_mut(|step| match &mut step.source {
+                StepSource::Lesson { lesson_index } => match new_index(*lesson_index) {
+                    Some(index) => {
+                        *lesson_index = index;
+                        true
+                    }
+                    None => false,
+                },
+                StepSource::WeakKeys => true,
+            });
+        }
+        self.selected_index = match self.selected_index {
+            // a removed lesson leaves the one after it selected
+            Some(index) if index < old_lesson_count => {
+                new_index(index).or_else(|| Some(index.min(self.lessons.len().checked_sub(1)?)))
+            }
+            // routines are listed after the lessons
+            Some(index) => Some(index - old_lesson_count + self.lessons.len()),
+            None => None,
+        };
+    }
+    /// Adds the record to the selected lesson and returns the achievements
+    /// earned with it. Without a selected lesson nothing is recorded.
+    pub fn add_record_to_current_session(
+        &mut self,
+        trainig_record: TrainingRecord,
+    ) -> Vec<Achievement> {
+        match self.selected_index {
+            Some(index) if index < self.lessons.len() => self.add_record(index, trainig_record),
+            _ => Vec::new(),
+        }
+    }
+    /// Adds the record to the lesson at `index` with the current daily goal
+    /// and returns the achievements earned with it.
+    pub fn add_record(
+        &mut self,
+        index: usize,
+        mut trainig_record: TrainingRecord,
+    ) -> Vec<Achievement> {
+        trainig_record.daily_goal = self.daily_goal;
+        let today = trainig_record.timestamp.naive_utc().date();
+        let records = self.training_records.entry(index).or_default();
+        records.push(trainig_record);
+        drop_old_recordings(records);
+        let streak = self.practice_streak(today);
+        let record = self.training_records[&index].last().unwrap();
+        let layout_name = self.layout.as_ref().map(|layout| layout.name.as_str());
+        self.profile.record_session(record, streak, layout_name)
+    }
+    /// Adds the record of a drill and returns the achievements earned with
+    /// it. Drills count for streaks, goals and weak keys, not for lessons.
+    pub fn add_drill_record(&mut self, mut training_record: TrainingRecord) -> Vec<Achievement> {
+        training_record.daily_goal = self.daily_goal;
+        let today = training_record.timestamp.naive_utc().date();
+        self.drill_records.push(training_record);
+        drop_old_recordings(&mut self.drill_records);
+        let streak = self.practice_streak(today);
+        let record = self.drill_records.last().unwrap();
+        let layout_name = self.layout.as_ref().map(|layout| layout.name.as_str());
+        self.profile.record_session(record, streak, layout_name)
+    }
+    pub fn drill_records(&self) -> &[TrainingRecord] {
+        &self.drill_records[..]
+    }
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+    pub fn practice_streak(&self, today: NaiveDate) -> PracticeStreak {
+        let timestamps: Vec<&DateTime<Utc>> =
+            self.all_records().map(|record| &record.timestamp).collect();
+        practice_streak(timestamps.into_iter(), today)
+    }
+    pub fn daily_goal(&self) -> Option<DailyGoal> {
+        self.daily_goal
+    }
+    pub fn set_daily_goal(&mut self, daily_goal: Option<DailyGoal>) {
+        self.daily_goal = daily_goal;
+    }
+    pub fn ghost(&self) -> Option<Ghost> {
+        self.ghost
+    }
+    pub fn set_ghost(&mut self, ghost: Option<Ghost>) {
+        self.ghost = ghost;
+    }
+    /// Speed of the ghost for a session on the lesson. Sessions outside of
+    /// the lesson list, and lessons not typed yet, have no personal best.
+    pub fn ghost_speed(&self, lesson_index: Option<usize>) -> Option<TypingSpeed> {
+        match self.ghost? {
+            Ghost::PersonalBest => self
+                .lesson_records(lesson_index?)
+                .iter()
+                .map(|record| record.stats.typing_speed)
+                .max_by_key(|speed| speed.characters_per_minute()),
+            Ghost::TargetWordsPerMinute(words_per_minute) => {
+                Some(TypingSpeed::WordsPerMinute(words_per_minute))
+            }
+        }
+    }
+    /// Practice per day over all lessons.
+    pub fn daily_practice(&self) -> BTreeMap<NaiveDate, DailyPractice> {
+        goals::daily_practice(self.all_records())
+    }
+    pub fn total_practice_seconds(&self) -> u32 {
+        self.all_records()
+            .map(|record| record.stats.duration_seconds)
+            .sum()
+    }
+    fn all_records(&self) -> impl Iterator<Item = &TrainingRecord> {
+        self.training_records
+            .values()
+            .flatten()
+            .chain(self.drill_records.iter())
+    }
+    pub fn personal_best(&self, index: usize) -> Option<u16> {
+        self.lesson_records(index)
+            .iter()
+            .map(|record| record.stats.typing_speed.words_per_minute())
+            .max()
+    }
+    /// The fastest session over all lessons as lesson index and WPM.
+    pub fn overall_personal_best(&self) -> Option<(usize, u16)> {
+        (0..self.lessons.len())
+            .filter_map(|index| Some((index, self.personal_best(index)?)))
+            .max_by_key(|(_, words_per_minute)| *words_per_minute)
     }
 }
 #[cfg(test)]
 mod test_selectable_session_list {
     use pretty_assertions::assert_eq;
 
+    use crate::core::routine::{StepLimit, StepSource};
+    use crate::core::stats::record;
+    use crate::core::stats::Recording;
     use crate::core::weighting_strategy::WeightingStrategy;
 
     use super::*;
@@ -104,9 +472,8 @@ mod test_selectable_session_list {
     #[test]
     fn test_select_next_lesson() {
         let mut unit = SelectableLessonList {
-            lessons: get_sample_lessons(),
             selected_index: Some(0),
-            training_records: HashMap::new(),
+            ..SelectableLessonList::new(get_sample_lessons())
         };
         unit.select_next_lesson();
         assert_eq!(unit.selected_index, Some(1))
@@ -114,29 +481,23 @@ mod test_selectable_session_list {
     #[test]
     fn test_select_next_lesson_when_last_index_is_selected() {
         let mut unit = SelectableLessonList {
-            lessons: get_sample_lessons(),
             selected_index: Some(1),
-            training_records: HashMap::new(),
+            ..SelectableLessonList::new(get_sample_lessons())
         };
         unit.select_next_lesson();
         assert_eq!(unit.selected_index, Some(1))
     }
     #[test]
     fn test_select_next_lesson_when_none_is_selected() {
-        let mut unit = SelectableLessonList {
-            lessons: get_sample_lessons(),
-            selected_index: None,
-            training_records: HashMap::new(),
-        };
+        let mut unit = SelectableLessonList::new(get_sample_lessons());
         unit.select_next_lesson();
         assert_eq!(unit.selected_index, Some(0))
     }
     #[test]
     fn test_select_prev_lesson() {
         let mut unit = SelectableLessonList {
-            lessons: get_sample_lessons(),
             selected_index: Some(1),
-            training_records: HashMap::new(),
+            ..SelectableLessonList::new(get_sample_lessons())
         };
         unit.select_prev_lesson();
         assert_eq!(unit.selected_index, Some(0))
@@ -144,39 +505,283 @@ mod test_selectable_session_list {
     #[test]
     fn test_select_prev_lesson_when_first_index_is_selected() {
         let mut unit = SelectableLessonList {
-            lessons: get_sample_lessons(),
             selected_index: Some(0),
-            training_records: HashMap::new(),
+            ..SelectableLessonList::new(get_sample_lessons())
         };
         unit.select_prev_lesson();
         assert_eq!(unit.selected_index, Some(0))
     }
     #[test]
     fn test_select_prev_lesson_when_none_is_selected() {
-        let mut unit = SelectableLessonList {
-            lessons: get_sample_lessons(),
-            selected_index: None,
-            training_records: HashMap::new(),
-        };
+        let mut unit = SelectableLessonList::new(get_sample_lessons());
         unit.select_prev_lesson();
         assert_eq!(unit.selected_index, Some(1))
     }
     #[test]
     fn test_get_current_lesson() {
         let unit = SelectableLessonList {
-            lessons: get_sample_lessons(),
             selected_index: Some(0),
-            training_records: HashMap::new(),
+            ..SelectableLessonList::new(get_sample_lessons())
         };
-        assert_eq!(unit.current_lesson(), unit.lessons.get(0))
+        assert_eq!(unit.current_lesson(), unit.lessons.first())
     }
     #[test]
     fn test_get_current_lesson_when_none_is_selected() {
-        let unit = SelectableLessonList {
-            lessons: get_sample_lessons(),
-            selected_index: None,
-            training_records: HashMap::new(),
-        };
+        let unit = SelectableLessonList::new(get_sample_lessons());
         assert_eq!(unit.current_lesson(), None)
     }
+
+    fn criteria() -> MasteryCriteria {
+        MasteryCriteria {
+            min_net_words_per_minute: 20,
+            min_accuracy: 0.9,
+            session_count: 1,
+        }
+    }
+    #[test]
+    fn test_lessons_are_unlocked_without_criteria() {
+        let unit = SelectableLessonList::new(get_sample_lessons());
+        assert_eq!(unit.lesson_state(1), LessonState::Unlocked)
+    }
+    #[test]
+    fn test_lesson_is_locked_until_previous_is_mastered() {
+        let mut unit =
+            SelectableLessonList::new(get_sample_lessons()).with_mastery_criteria(criteria());
+        unit.select_next_lesson();
+        assert_eq!(unit.lesson_state(0), LessonState::Unlocked);
+        assert_eq!(unit.lesson_state(1), LessonState::Locked);
+        unit.add_record_to_current_session(record(25, 0));
+        assert_eq!(unit.lesson_state(0), LessonState::Mastered);
+        assert_eq!(unit.lesson_state(1), LessonState::Unlocked)
+    }
+    #[test]
+    fn test_nothing_is_recorded_without_a_selected_lesson() {
+        let mut unit = SelectableLessonList::new(get_sample_lessons());
+        assert!(unit.add_record_to_current_session(record(25, 0)).is_empty());
+        unit.add_record(1, record(25, 0));
+        assert!(unit.lesson_records(0).is_empty());
+        assert_eq!(unit.lesson_records(1).len(), 1)
+    }
+    #[test]
+    fn test_only_the_latest_recordings_are_kept() {
+        let mut unit = SelectableLessonList::new(get_sample_lessons());
+        for _ in 0..KEPT_RECORDINGS + 2 {
+            let mut training_record = record(25, 0);
+            training_record.recording = Some(Recording {
+                content: String::from("a"),
+                keystrokes: Vec::new(),
+                time_limit_seconds: None,
+            });
+            unit.add_record(0, training_record);
+        }
+        let kept: Vec<bool> = unit
+            .lesson_records(0)
+            .iter()
+            .map(|record| record.recording.is_some())
+            .collect();
+        assert_eq!(kept[..2], [false, false]);
+        assert!(kept[2..].iter().all(|is_kept| *is_kept))
+    }
+    #[test]
+    fn test_drill_records_count_for_streaks_but_not_for_lessons() {
+        let mut unit =
+            SelectableLessonList::new(get_sample_lessons()).with_mastery_criteria(criteria());
+        let today = Utc::now().naive_utc().date();
+        unit.add_drill_record(record(25, 0));
+        assert_eq!(unit.drill_records().len(), 1);
+        assert_eq!(unit.practice_streak(today).current_days, 1);
+        assert_eq!(unit.lesson_state(0), LessonState::Unlocked);
+        assert!(unit.lesson_records(0).is_empty())
+    }
+    #[test]
+    fn test_lesson_after_a_lesson_without_criteria_is_unlocked() {
+        let mut lessons = get_sample_lessons();
+        lessons[1] = lessons[1].clone().with_mastery_criteria(criteria());
+        let unit = SelectableLessonList::new(lessons);
+        assert_eq!(unit.lesson_state(0), LessonState::Unlocked);
+        assert_eq!(unit.lesson_state(1), LessonState::Unlocked)
+    }
+    #[test]
+    fn test_lesson_criteria_override_course_criteria() {
+        let lessons = get_sample_lessons();
+        let strict_lesson = lessons[0].clone().with_mastery_criteria(MasteryCriteria {
+            min_net_words_per_minute: 50,
+            min_accuracy: 0.9,
+            session_count: 1,
+        });
+        let mut unit = SelectableLessonList::new(vec![strict_lesson, lessons[1].clone()])
+            .with_mastery_criteria(criteria());
+        unit.select_next_lesson();
+        unit.add_record_to_current_session(record(25, 0));
+        assert_eq!(unit.lesson_state(0), LessonState::Unlocked);
+        assert_eq!(unit.lesson_state(1), LessonState::Locked)
+    }
+    #[test]
+    fn test_auto_advance_selects_next_lesson_once_mastered() {
+        let mut unit =
+            SelectableLessonList::new(get_sample_lessons()).with_mastery_criteria(criteria());
+        unit.toggle_auto_advance();
+        unit.select_next_lesson();
+        unit.add_record_to_current_session(record(10, 0));
+        assert!(!unit.advance_if_mastered());
+        unit.add_record_to_current_session(record(25, 0));
+        assert!(unit.advance_if_mastered());
+        assert_eq!(unit.selected_index(), Some(1))
+    }
+    #[test]
+    fn test_no_auto_advance_when_disabled() {
+        let mut unit =
+            SelectableLessonList::new(get_sample_lessons()).with_mastery_criteria(criteria());
+        unit.select_next_lesson();
+        unit.add_record_to_current_session(record(25, 0));
+        assert!(!unit.advance_if_mastered());
+        assert_eq!(unit.selected_index(), Some(0))
+    }
+    #[test]
+    fn test_personal_bests_per_lesson_and_overall() {
+        let mut unit = SelectableLessonList::new(get_sample_lessons());
+        unit.select_next_lesson();
+        unit.add_record_to_current_session(record(25, 0));
+        unit.add_record_to_current_session(record(35, 0));
+        unit.select_next_lesson();
+        unit.add_record_to_current_session(record(30, 0));
+        assert_eq!(unit.personal_best(0), Some(35));
+        assert_eq!(unit.personal_best(1), Some(30));
+        assert_eq!(unit.overall_personal_best(), Some((0, 35)))
+    }
+    #[test]
+    fn test_ghost_speed_is_the_personal_best_or_the_target() {
+        let mut unit =
+            SelectableLessonList::new(get_sample_lessons()).with_ghost(Ghost::PersonalBest);
+        unit.select_next_lesson();
+        unit.add_record_to_current_session(record(25, 0));
+        unit.add_record_to_current_session(record(35, 0));
+        assert_eq!(
+            unit.ghost_speed(Some(0)),
+            Some(TypingSpeed::WordsPerMinute(35))
+        );
+        assert_eq!(unit.ghost_speed(Some(1)), None);
+        assert_eq!(unit.ghost_speed(None), None);
+        unit.set_ghost(Some(Ghost::TargetWordsPerMinute(50)));
+        assert_eq!(
+            unit.ghost_speed(None),
+            Some(TypingSpeed::WordsPerMinute(50))
+        );
+        unit.set_ghost(None);
+        assert_eq!(unit.ghost_speed(Some(0)), None)
+    }
+    #[test]
+    fn test_adding_records_earns_achievements() {
+        let mut unit =
+            SelectableLessonList::new(get_sample_lessons()).with_layout(KeyboardLayout::bone());
+        unit.select_next_lesson();
+        let earned = unit.add_record_to_current_session(record(45, 3));
+        assert_eq!(earned.len(), 1);
+        assert_eq!(earned[0].layout, Some(String::from("Bone")));
+        assert_eq!(unit.profile().achievements, earned)
+    }
+    #[test]
+    fn test_practice_time_is_summed_over_all_lessons() {
+        let mut unit = SelectableLessonList::new(get_sample_lessons());
+        unit.select_lesson(0);
+        unit.add_record_to_current_session(record(20, 0));
+        unit.select_lesson(1);
+        unit.add_record_to_current_session(record(30, 0));
+        assert_eq!(unit.total_practice_seconds(), 120);
+        let practice: Vec<u32> = unit
+            .daily_practice()
+            .values()
+            .map(|day| day.sessions)
+            .collect();
+        assert_eq!(practice, vec![2])
+    }
+    #[test]
+    fn test_records_keep_the_daily_goal_of_their_time() {
+        let mut unit =
+            SelectableLessonList::new(get_sample_lessons()).with_daily_goal(DailyGoal::Sessions(3));
+        unit.add_record(0, record(20, 0));
+        unit.set_daily_goal(None);
+        unit.add_drill_record(record(20, 0));
+        assert_eq!(
+            unit.lesson_records(0)[0].daily_goal,
+            Some(DailyGoal::Sessions(3))
+        );
+        assert_eq!(unit.drill_records()[0].daily_goal, None)
+    }
+    #[test]
+    fn test_routines_are_selected_after_the_lessons() {
+        let routine = Routine::new("Warm-up").then(
+            StepSource::Lesson { lesson_index: 0 },
+            StepLimit::Seconds(60),
+        );
+        let mut unit =
+            SelectableLessonList::new(get_sample_lessons()).with_routines(vec![routine.clone()]);
+        unit.select_lesson(1);
+        unit.select_next_lesson();
+        assert_eq!(unit.current_lesson(), None);
+        assert_eq!(unit.current_routine(), Some(&routine));
+        unit.select_next_lesson();
+        assert_eq!(unit.selected_index(), Some(2));
+        unit.select_prev_lesson();
+        assert_eq!(unit.current_lesson().map(Lesson::name), Some("Lesson 2"))
+    }
+    #[test]
+    fn test_records_and_routine_steps_move_with_their_lessons() {
+        let routine = Routine::new("Warm-up")
+            .then(
+                StepSource::Lesson { lesson_index: 0 },
+                StepLimit::Seconds(60),
+            )
+            .then(
+                StepSource::Lesson { lesson_index: 1 },
+                StepLimit::Seconds(60),
+            );
+        let mut unit = SelectableLessonList::new(get_sample_lessons()).with_routines(vec![routine]);
+        unit.select_lesson(1);
+        unit.add_record_to_current_session(record(30, 0));
+
+        let lesson_0 = unit.lessons()[0]
+            .clone()
+            .with_name(String::from("Lesson 0"));
+        unit.insert_lesson(0, lesson_0);
+        assert_eq!(unit.current_lesson().map(Lesson::name), Some("Lesson 2"));
+        assert_eq!(unit.personal_best(2), Some(30));
+        unit.swap_lessons(1, 2);
+        assert_eq!(unit.selected_index(), Some(1));
+        assert_eq!(unit.personal_best(1), Some(30));
+        let steps: Vec<StepSource> = unit.routines()[0]
+            .steps
+            .iter()
+            .map(|step| step.source.clone())
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                StepSource::Lesson { lesson_index: 2 },
+                StepSource::Lesson { lesson_index: 1 }
+            ]
+        );
+
+        assert_eq!(
+            unit.remove_lesson(1).as_ref().map(Lesson::name),
+            Some("Lesson 2")
+        );
+        assert!(unit.lesson_records(1).is_empty());
+        assert_eq!(unit.routines()[0].steps.len(), 1);
+        assert_eq!(unit.current_lesson().map(Lesson::name), Some("Lesson 1"))
+    }
+    #[test]
+    fn test_selected_routine_stays_selected_when_lessons_change() {
+        let mut unit = SelectableLessonList::new(get_sample_lessons())
+            .with_routines(vec![Routine::new("Warm-up")]);
+        unit.select_lesson_entry(2);
+        unit.remove_lesson(0);
+        assert_eq!(
+            unit.current_routine().map(|routine| routine.name.as_str()),
+            Some("Warm-up")
+        );
+        unit.insert_lesson(5, get_sample_lessons().remove(0));
+        assert_eq!(unit.selected_index(), Some(2));
+        assert_eq!(unit.lessons()[1].name(), "Lesson 1")
+    }
 }
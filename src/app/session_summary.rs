@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+use chrono::Duration;
+
+use crate::app::training_session::TrainingSession;
+use crate::core::achievements::Achievement;
+use crate::core::stats::{TrainingRecord, TrainingStatistics};
+use crate::core::typing_errors::TypingErrors;
+use crate::core::typing_speed::TypingSpeed;
+
+const ROLLING_AVERAGE_SESSIONS: usize = 5;
+const LISTED_KEYS: usize = 3;
+
+/// Results of a finished session compared to the earlier sessions of the
+/// same lesson.
+pub struct SessionSummary {
+    pub lesson_name: String,
+    pub stats: TrainingStatistics,
+    pub slowest_keys: Vec<(String, Duration)>,
+    pub most_missed_keys: Vec<(String, u16)>,
+    pub personal_best_words_per_minute: Option<u16>,
+    pub rolling_average_words_per_minute: Option<f64>,
+    pub new_achievements: Vec<Achievement>,
+    /// The seed the text was generated from, to share it.
+    pub seed: Option<u64>,
+}
+
+impl SessionSummary {
+    /// `previous_records` must not contain the record of the finished session.
+    pub fn new(
+        lesson_name: &str,
+        session: &TrainingSession,
+        previous_records: &[TrainingRecord],
+    ) -> SessionSummary {
+        let stats = session.stats();
+        let mut most_missed_keys: Vec<(String, u16)> = stats
+            .errors
+            .errors_by_key
+            .iter()
+            .map(|(key, errors)| (key.clone(), *errors))
+            .collect();
+        most_missed_keys.sort_by_key(|(_, errors)| std::cmp::Reverse(*errors));
+        most_missed_keys.truncate(LISTED_KEYS);
+        let mut slowest_keys = session.slowest_keys();
+        slowest_keys.truncate(LISTED_KEYS);
+
+        let personal_best_words_per_minute = previous_records
+            .iter()
+            .map(|record| record.stats.typing_speed.words_per_minute())
+            .max();
+        let recent_records = &previous_records[previous_records
+            .len()
+            .saturating_sub(ROLLING_AVERAGE_SESSIONS)..];
+        let rolling_average_words_per_minute = if recent_records.is_empty() {
+            None
+        } else {
+            let total: f64 = recent_records
+                .iter()
+                .map(|record| record.stats.typing_speed.words_per_minute() as f64)
+                .sum();
+            Some(total / recent_records.len() as f64)
+        };
+        SessionSummary {
+            lesson_name: lesson_name.to_string(),
+            stats,
+            slowest_keys,
+            most_missed_keys,
+            personal_best_words_per_minute,
+            rolling_average_words_per_minute,
+            new_achievements: Vec::new(),
+            seed: session.seed(),
+        }
+    }
+
+    pub fn is_personal_best(&self) -> bool {
+        match self.personal_best_words_per_minute {
+            Some(best) => self.stats.typing_speed.words_per_minute() > best,
+            None => true,
+        }
+    }
+}
+
+/// Results of the steps of a routine and of the routine as a whole.
+pub struct RoutineSummary {
+    pub routine_name: String,
+    pub steps: Vec<SessionSummary>,
+}
+
+impl RoutineSummary {
+    pub fn new(routine_name: &str) -> RoutineSummary {
+        RoutineSummary {
+            routine_name: routine_name.to_string(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Statistics as if all steps had been typed in one session.
+    pub fn total_stats(&self) -> TrainingStatistics {
+        let mut errors = TypingErrors::default();
+        let mut typed_characters = 0;
+        let mut duration_seconds = 0;
+        for step in self.steps.iter() {
+            errors.total_error_count += step.stats.errors.total_error_count;
+            for (key, key_errors) in step.stats.errors.errors_by_key.iter() {
+                *errors.errors_by_key.entry(key.clone()).or_default() += key_errors;
+            }
+            typed_characters += step.stats.typed_characters;
+            duration_seconds += step.stats.duration_seconds;
+        }
+        let characters_per_minute = match duration_seconds {
+            0 => 0,
+            _ => 60 * typed_characters / duration_seconds,
+        };
+        TrainingStatistics {
+            errors,
+            typing_speed: TypingSpeed::CharactersPerMinute(characters_per_minute as u16),
+            layers: BTreeMap::new(),
+            typed_characters,
+            duration_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_session_summary {
+    use pretty_assertions::assert_eq;
+
+    use crate::core::stats::record;
+    use crate::wrapper::fake_clock::FakeClock;
+
+    use super::*;
+
+    /// Types "abcab" at 10 characters per second, missing `b` twice.
+    fn finished_session() -> TrainingSession {
+        let mut session = TrainingSession::new(String::from("abcab"));
+        for c in "axxbcab".chars() {
+            session.handle_key(c);
+            FakeClock::advance(Duration::milliseconds(100));
+        }
+        session
+    }
+
+    #[test]
+    fn compares_with_previous_sessions() {
+        let previous_records: Vec<TrainingRecord> = [10, 50, 20, 30, 40, 60]
+            .iter()
+            .map(|wpm| record(*wpm, 0))
+            .collect();
+        let summary = SessionSummary::new("Lesson", &finished_session(), &previous_records);
+        assert_eq!(summary.personal_best_words_per_minute, Some(60));
+        assert_eq!(summary.rolling_average_words_per_minute, Some(40.0))
+    }
+    #[test]
+    fn first_session_is_a_personal_best() {
+        let summary = SessionSummary::new("Lesson", &finished_session(), &[]);
+        assert_eq!(summary.personal_best_words_per_minute, None);
+        assert_eq!(summary.rolling_average_words_per_minute, None);
+        assert!(summary.is_personal_best())
+    }
+    #[test]
+    fn lists_most_missed_keys() {
+        let summary = SessionSummary::new("Lesson", &finished_session(), &[]);
+        assert_eq!(summary.most_missed_keys, vec![(String::from("b"), 2)])
+    }
+    #[test]
+    fn routine_totals_add_up_all_steps() {
+        let mut routine_summary = RoutineSummary::new("Warm-up");
+        routine_summary
+            .steps
+            .push(SessionSummary::new("Lesson", &finished_session(), &[]));
+        routine_summary
+            .steps
+            .push(SessionSummary::new("Lesson", &finished_session(), &[]));
+        let total_stats = routine_summary.total_stats();
+        assert_eq!(total_stats.typed_characters, 10);
+        assert_eq!(total_stats.errors.total_error_count, 4);
+        assert_eq!(total_stats.errors.errors_by_key.get("b").copied(), Some(4))
+    }
+}
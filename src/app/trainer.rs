@@ -1,90 +1,562 @@
-use std::fs::OpenOptions;
-
-use serde_json;
-use termion::event::Key;
+use std::collections::VecDeque;
+use std::path::PathBuf;
 
+use crate::app::lesson_editor::{DraftField, DraftOrigin, LessonDraft};
+use crate::app::persistence::{load_lesson_list, save_lesson_list};
+use crate::app::practice_planner::{plan_practice, weak_key_drill, PlannedSession};
+use crate::app::replay::Replay;
 use crate::app::selectable_session_list::SelectableLessonList;
+use crate::app::session_summary::{RoutineSummary, SessionSummary};
 use crate::app::training_session::TrainingSession;
-use crate::core::enums::{AppState, OptionalInput};
-use crate::core::lesson::Lesson;
+use crate::core::enums::{AppState, Key, OptionalInput};
+use crate::core::ghost::Ghost;
+use crate::core::goals::DailyGoal;
+use crate::core::input::InputEvent;
+use crate::core::lesson::{lesson_of_the_day_seed, Lesson};
+use crate::core::routine::{Routine, StepSource};
+use crate::core::stats::Recording;
+#[cfg(not(test))]
+use crate::wrapper::clock::Clock;
+#[cfg(test)]
+use crate::wrapper::fake_clock::FakeClock as Clock;
 
 pub struct TrainerApp {
     pub lesson_list: SelectableLessonList,
     lesson_progress: TrainingSession,
     state: AppState,
-    file_path: String,
+    /// The save file, None for apps created with `new` and after a save
+    /// file could not be read, so that it is not overwritten.
+    file_path: Option<PathBuf>,
+    show_keyboard: bool,
+    practice_plan: VecDeque<PlannedSession>,
+    planned_session: Option<PlannedSession>,
+    session_summary: Option<SessionSummary>,
+    suspended_session: Option<SuspendedSession>,
+    routine_run: Option<RoutineRun>,
+    routine_summary: Option<RoutineSummary>,
+    /// Message about a recoverable failure, shown until the next key press.
+    notification: Option<String>,
+    replay: Option<Replay>,
+    /// The state to return to when the replay is left.
+    replay_return_state: Option<AppState>,
+    /// The lesson edited in the lesson editor, None while choosing one.
+    lesson_draft: Option<LessonDraft>,
+    /// A lesson to be deleted once it is confirmed.
+    pending_deletion: Option<usize>,
+    /// The seed typed in the lesson selection to generate a shared text.
+    seed_input: Option<String>,
+}
+
+/// Where an unfinished session left with Esc belongs to. The session itself
+/// stays in `lesson_progress` until another one is started.
+struct SuspendedSession {
+    lesson_index: Option<usize>,
+    planned_session: Option<PlannedSession>,
+    routine_run: Option<RoutineRun>,
+}
+
+/// A routine whose steps are being typed back to back.
+struct RoutineRun {
+    routine: Routine,
+    /// The lesson of the current step, None for drills. Steps do not change
+    /// the selected entry, which stays the routine.
+    lesson_index: Option<usize>,
+    next_step: usize,
+    summary: RoutineSummary,
 }
 
 impl TrainerApp {
     pub fn new(lessons: Vec<Lesson>) -> TrainerApp {
+        TrainerApp::from_lesson_list(SelectableLessonList::new(lessons))
+    }
+    fn from_lesson_list(lesson_list: SelectableLessonList) -> TrainerApp {
         TrainerApp {
-            lesson_list: SelectableLessonList::new(lessons),
+            lesson_list,
             lesson_progress: TrainingSession::default(),
             state: AppState::LessonSelection,
-            file_path: String::new(),
+            file_path: None,
+            show_keyboard: true,
+            practice_plan: VecDeque::new(),
+            planned_session: None,
+            session_summary: None,
+            suspended_session: None,
+            routine_run: None,
+            routine_summary: None,
+            notification: None,
+            replay: None,
+            replay_return_state: None,
+            lesson_draft: None,
+            pending_deletion: None,
+            seed_input: None,
         }
     }
-    pub fn load(file_path: String) -> Result<TrainerApp, anyhow::Error> {
-        let file = OpenOptions::new().read(true).open(&file_path)?;
-        let data = serde_json::from_reader(file)?;
-        Ok(TrainerApp {
-            lesson_list: data,
-            lesson_progress: TrainingSession::default(),
-            state: AppState::LessonSelection,
+    /// Starts with the save file at `file_path`. If it is missing or cannot
+    /// be read, the app starts without lessons and shows why. A save file
+    /// that cannot be read is left untouched.
+    pub fn load(file_path: impl Into<PathBuf>) -> TrainerApp {
+        let file_path = file_path.into();
+        let (lesson_list, file_path, notification) = match load_lesson_list(&file_path) {
+            Ok(lesson_list) => (lesson_list, Some(file_path), None),
+            Err(error) if !file_path.exists() => {
+                log::warn!("{:#}", error);
+                let notification = format!(
+                    "{} does not exist yet, create lessons with the lesson editor (e)",
+                    file_path.display()
+                );
+                (
+                    SelectableLessonList::new(Vec::new()),
+                    Some(file_path),
+                    Some(notification),
+                )
+            }
+            Err(error) => {
+                log::error!("{:#}", error);
+                let notification = format!(
+                    "{:#}. Starting without lessons, nothing is saved until it is fixed",
+                    error
+                );
+                (
+                    SelectableLessonList::new(Vec::new()),
+                    None,
+                    Some(notification),
+                )
+            }
+        };
+        let mut app = TrainerApp {
             file_path,
-        })
+            notification,
+            ..TrainerApp::from_lesson_list(lesson_list)
+        };
+        app.show_practice_plan();
+        app
+    }
+    /// Writes the save file, apps without one have nothing to save.
+    pub fn save(&self) -> anyhow::Result<()> {
+        match &self.file_path {
+            Some(file_path) => save_lesson_list(&self.lesson_list, file_path),
+            None => Ok(()),
+        }
+    }
+    /// Saves after a finished session so that a crash loses no records.
+    fn save_progress(&mut self) {
+        if let Err(error) = self.save() {
+            log::error!("saving progress failed: {:#}", error);
+            self.notify(format!("Could not save progress: {:#}", error));
+        }
     }
-    pub fn save(&self) {
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&self.file_path)
-            .unwrap();
-        serde_json::to_writer(file, &self.lesson_list).unwrap();
+    pub fn notify(&mut self, message: impl Into<String>) {
+        self.notification = Some(message.into());
+    }
+    pub fn notification(&self) -> Option<&str> {
+        self.notification.as_deref()
     }
     pub fn state(&self) -> &AppState {
         &self.state
     }
     pub fn lessons(&self) -> &[Lesson] {
-        &self.lesson_list.lessons()
+        self.lesson_list.lessons()
     }
+    /// The session shown in the training panel, the replayed one during a
+    /// replay.
     pub fn lesson_progress(&self) -> &TrainingSession {
-        &self.lesson_progress
+        match &self.replay {
+            Some(replay) if self.state == AppState::Replay => replay.session(),
+            _ => &self.lesson_progress,
+        }
+    }
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+    pub fn lesson_draft(&self) -> Option<&LessonDraft> {
+        self.lesson_draft.as_ref()
+    }
+    pub fn pending_deletion(&self) -> Option<usize> {
+        self.pending_deletion
+    }
+    pub fn seed_input(&self) -> Option<&str> {
+        self.seed_input.as_deref()
+    }
+    pub fn show_keyboard(&self) -> bool {
+        self.show_keyboard
+    }
+    pub fn session_summary(&self) -> Option<&SessionSummary> {
+        self.session_summary.as_ref()
+    }
+    pub fn routine_summary(&self) -> Option<&RoutineSummary> {
+        self.routine_summary.as_ref()
+    }
+    /// Name of the running routine with the number of the current step and
+    /// the number of steps.
+    pub fn routine_progress(&self) -> Option<(&str, usize, usize)> {
+        let routine_run = self.routine_run.as_ref()?;
+        Some((
+            routine_run.routine.name.as_str(),
+            routine_run.next_step,
+            routine_run.routine.steps.len(),
+        ))
+    }
+    pub fn has_suspended_session(&self) -> bool {
+        self.suspended_session.is_some()
+    }
+    pub fn practice_plan(&self) -> &VecDeque<PlannedSession> {
+        &self.practice_plan
     }
 
     pub fn tick(&mut self, optional_input: OptionalInput) {
         match optional_input {
-            OptionalInput::InputKey(key) => self.handle_input(key),
-            OptionalInput::NoInput => {}
+            OptionalInput::InputKey(event) => self.handle_input(event),
+            // the layout is computed on every draw
+            OptionalInput::Resize => {}
+            OptionalInput::NoInput => {
+                if let Some(replay) = self.replay.as_mut() {
+                    replay.advance_to(Clock::now());
+                }
+                if self.state == AppState::Training {
+                    // the wrong key only flashes until the next tick
+                    self.lesson_progress.clear_wrong_input();
+                    self.lesson_progress.pause_if_idle();
+                    // time limited sessions end without a keystroke
+                    if self.lesson_progress.is_finished() {
+                        self.finish_session();
+                    }
+                }
+            }
         }
     }
-    fn handle_input(&mut self, input_key: Key) {
+    fn handle_input(&mut self, event: InputEvent) {
+        let input_key = event.key;
+        self.notification = None;
+        if input_key == Key::Ctrl('k') {
+            self.show_keyboard = !self.show_keyboard;
+            return;
+        }
         match self.state {
-            AppState::Training => self.handle_training(input_key),
+            AppState::Planner => self.handle_planner(input_key),
+            AppState::Training => self.handle_training(event),
+            AppState::Replay => self.handle_replay(input_key),
+            AppState::SessionSummary => self.handle_session_summary(input_key),
+            AppState::Profile => self.handle_profile(input_key),
+            AppState::Goals => self.handle_goals(input_key),
             AppState::LessonSelection => self.handle_lesson_selection(input_key),
+            AppState::LessonEditor => self.handle_lesson_editor(input_key),
             AppState::Terminated => {}
         }
     }
 
-    fn handle_training(&mut self, input_key: Key) {
+    fn handle_training(&mut self, event: InputEvent) {
+        let input_key = event.key;
+        // the time limit may run out between two ticks
+        if self.lesson_progress.is_finished() {
+            self.finish_session();
+            return;
+        }
+        // a key after the idle timeout resumes the session like one after a
+        // tick paused it, whether or not a tick came in between
+        self.lesson_progress.pause_if_idle_at(event.timestamp);
+        if self.lesson_progress.is_paused() && input_key != Key::Esc {
+            // the key only resumes the session, it is not typed
+            self.lesson_progress.resume_at(event.timestamp);
+            return;
+        }
         match input_key {
             Key::Esc => {
+                self.suspend_session();
                 self.state = AppState::LessonSelection;
             }
-            Key::Char(c) => {
-                self.lesson_progress.handle_key(c);
+            Key::Ctrl('p') => {
+                self.lesson_progress.pause();
+            }
+            Key::Char(_) => {
+                self.lesson_progress.handle_event(&event);
                 if self.lesson_progress.is_finished() {
-                    self.lesson_list
-                        .add_record_to_current_session(self.lesson_progress.training_record());
-                    self.start_session();
+                    self.finish_session();
                 }
             }
             _ => {}
         }
     }
 
+    /// Keeps a started session to be resumed later, leaving the planned
+    /// sessions behind.
+    fn suspend_session(&mut self) {
+        let planned_session = self.planned_session.take();
+        let routine_run = self.routine_run.take();
+        if !self.lesson_progress.is_started() || self.lesson_progress.is_finished() {
+            return;
+        }
+        self.lesson_progress.pause();
+        self.suspended_session = Some(SuspendedSession {
+            lesson_index: self.lesson_list.selected_index(),
+            planned_session,
+            routine_run,
+        });
+    }
+
+    fn resume_session(&mut self) {
+        if let Some(suspended_session) = self.suspended_session.take() {
+            if let Some(lesson_index) = suspended_session.lesson_index {
+                self.lesson_list.select_lesson(lesson_index);
+            }
+            self.planned_session = suspended_session.planned_session;
+            self.routine_run = suspended_session.routine_run;
+            self.state = AppState::Training;
+        }
+    }
+
+    /// Records the finished session and shows its summary.
+    fn finish_session(&mut self) {
+        let is_drill = matches!(
+            self.planned_session,
+            Some(PlannedSession::WeakKeyDrill { .. })
+        );
+        let lesson_index = self.session_lesson_index();
+        let mut summary = SessionSummary::new(
+            self.session_lesson().map(Lesson::name).unwrap_or_default(),
+            &self.lesson_progress,
+            lesson_index.map_or(&[], |index| self.lesson_list.lesson_records(index)),
+        );
+        let record = self.lesson_progress.training_record();
+        summary.new_achievements = match lesson_index {
+            Some(index) => self.lesson_list.add_record(index, record),
+            None if is_drill => self.lesson_list.add_drill_record(record),
+            None => Vec::new(),
+        };
+        self.save_progress();
+        if let Some(routine_run) = self.routine_run.as_mut() {
+            routine_run.summary.steps.push(summary);
+            self.start_next_routine_step();
+            return;
+        }
+        self.routine_summary = None;
+        self.session_summary = Some(summary);
+        self.state = AppState::SessionSummary;
+    }
+
+    fn start_routine(&mut self) {
+        let routine = match self.lesson_list.current_routine() {
+            Some(routine) => routine.clone(),
+            None => return,
+        };
+        self.planned_session = None;
+        self.routine_run = Some(RoutineRun {
+            summary: RoutineSummary::new(&routine.name),
+            routine,
+            lesson_index: None,
+            next_step: 0,
+        });
+        self.start_next_routine_step();
+    }
+
+    /// Starts the next step of the running routine, steps without anything
+    /// to type are skipped. Shows the combined summary after the last step.
+    fn start_next_routine_step(&mut self) {
+        while let Some(routine_run) = self.routine_run.as_mut() {
+            let step = match routine_run.routine.steps.get(routine_run.next_step) {
+                Some(step) => step.clone(),
+                None => return self.finish_routine(),
+            };
+            routine_run.next_step += 1;
+            let lesson = match step.source {
+                StepSource::Lesson { lesson_index } => {
+                    match self.lesson_list.lessons().get(lesson_index).cloned() {
+                        Some(lesson) => {
+                            routine_run.lesson_index = Some(lesson_index);
+                            self.planned_session = None;
+                            lesson
+                        }
+                        None => continue,
+                    }
+                }
+                StepSource::WeakKeys => match weak_key_drill(&self.lesson_list, Clock::now()) {
+                    Some(lesson) => {
+                        routine_run.lesson_index = None;
+                        self.planned_session = Some(PlannedSession::WeakKeyDrill {
+                            lesson: Box::new(lesson.clone()),
+                        });
+                        lesson
+                    }
+                    None => continue,
+                },
+            };
+            let seed = rand::random();
+            let content = lesson.generate_content_with_seed(step.limit.content_length(), seed);
+            let session = self
+                .session_with_content(&lesson, content)
+                .with_time_limit(step.limit.time_limit())
+                .with_seed(Some(seed));
+            return self.begin_training(session);
+        }
+    }
+
+    fn finish_routine(&mut self) {
+        let routine_run = match self.routine_run.take() {
+            Some(routine_run) => routine_run,
+            None => return,
+        };
+        self.planned_session = None;
+        if routine_run.summary.steps.is_empty() {
+            self.state = AppState::LessonSelection;
+            return;
+        }
+        self.routine_summary = Some(routine_run.summary);
+        self.state = AppState::SessionSummary;
+    }
+
+    fn handle_session_summary(&mut self, input_key: Key) {
+        match input_key {
+            Key::Esc => {
+                self.planned_session = None;
+                self.state = AppState::LessonSelection;
+            }
+            Key::Char('r') => {
+                self.retry_session();
+            }
+            Key::Char('t') => {
+                self.retry_same_text();
+            }
+            Key::Char('v') => {
+                self.start_replay(self.lesson_progress.recording());
+            }
+            Key::Enter | Key::Char('c') => {
+                self.continue_after_session();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_replay(&mut self, input_key: Key) {
+        let replay = match self.replay.as_mut() {
+            Some(replay) => replay,
+            None => return,
+        };
+        match input_key {
+            Key::Esc => {
+                self.replay = None;
+                self.state = self
+                    .replay_return_state
+                    .take()
+                    .unwrap_or(AppState::LessonSelection);
+            }
+            Key::Char('+') => replay.faster(),
+            Key::Char('-') => replay.slower(),
+            _ => {}
+        }
+    }
+
+    /// Plays the recording back with the compose sequences and layout of the
+    /// lesson it was typed in.
+    fn start_replay(&mut self, recording: Recording) {
+        let session = match self.session_lesson() {
+            Some(lesson) => self.session_with_content(lesson, recording.content.clone()),
+            None => TrainingSession::new(recording.content.clone()),
+        };
+        self.replay = Some(Replay::new(&recording, session, Clock::now()));
+        self.replay_return_state = Some(std::mem::replace(&mut self.state, AppState::Replay));
+    }
+
+    /// Replays the latest recorded session of the selected lesson.
+    fn replay_last_session(&mut self) {
+        let recording = self
+            .lesson_list
+            .current_lesson_records()
+            .iter()
+            .rev()
+            .find_map(|record| record.recording.clone());
+        match recording {
+            Some(recording) => self.start_replay(recording),
+            None => self.notify("There is no recorded session of this lesson yet"),
+        }
+    }
+
+    fn retry_session(&mut self) {
+        if self.routine_summary.is_some() {
+            return self.start_routine();
+        }
+        match &self.planned_session {
+            Some(PlannedSession::WeakKeyDrill { lesson }) => {
+                let session = self.create_session(lesson, rand::random());
+                self.begin_training(session);
+            }
+            _ => self.start_session(),
+        }
+    }
+
+    /// Types the content of the finished session again.
+    fn retry_same_text(&mut self) {
+        if self.routine_summary.is_some() {
+            self.notify(
+                "Only the text of a single session can be typed again, r retries the routine",
+            );
+            return;
+        }
+        let content = self.lesson_progress.lesson_content.clone();
+        let seed = self.lesson_progress.seed();
+        if let Some(lesson) = self.session_lesson() {
+            let session = self.session_with_content(lesson, content).with_seed(seed);
+            self.begin_training(session);
+        }
+    }
+
+    fn continue_after_session(&mut self) {
+        if self.routine_summary.is_some() {
+            self.state = AppState::LessonSelection;
+            return;
+        }
+        if self.planned_session.take().is_some() {
+            self.start_next_planned_session();
+        } else {
+            self.lesson_list.advance_if_mastered();
+            self.start_session();
+        }
+    }
+
+    fn handle_profile(&mut self, input_key: Key) {
+        if let Key::Esc | Key::Char('o') = input_key {
+            self.state = AppState::LessonSelection;
+        }
+    }
+
+    fn handle_goals(&mut self, input_key: Key) {
+        let daily_goal = self.lesson_list.daily_goal();
+        match input_key {
+            Key::Esc | Key::Char('g') => {
+                self.state = AppState::LessonSelection;
+            }
+            Key::Char('t') => {
+                let next = daily_goal.map_or_else(DailyGoal::default, DailyGoal::next_kind);
+                self.lesson_list.set_daily_goal(Some(next));
+            }
+            Key::Char('+') => {
+                self.lesson_list
+                    .set_daily_goal(daily_goal.map(DailyGoal::increased));
+            }
+            Key::Char('-') => {
+                self.lesson_list
+                    .set_daily_goal(daily_goal.map(DailyGoal::decreased));
+            }
+            Key::Char('x') => {
+                self.lesson_list.set_daily_goal(None);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_planner(&mut self, input_key: Key) {
+        match input_key {
+            Key::Esc => {
+                self.state = AppState::LessonSelection;
+            }
+            Key::Enter => {
+                self.start_next_planned_session();
+            }
+            _ => {}
+        }
+    }
+
     fn handle_lesson_selection(&mut self, input_key: Key) {
+        if self.seed_input.is_some() {
+            return self.handle_seed_input(input_key);
+        }
         match input_key {
             Key::Esc => {
                 self.state = AppState::Terminated;
@@ -95,20 +567,518 @@ impl TrainerApp {
             Key::Up => {
                 self.lesson_list.select_prev_lesson();
             }
-            Key::Char('\n') => {
-                self.start_session();
+            Key::Enter => {
+                if self.lesson_list.current_routine().is_some() {
+                    self.start_routine();
+                } else {
+                    self.start_session();
+                }
+            }
+            Key::Char('a') => {
+                self.lesson_list.toggle_auto_advance();
+            }
+            Key::Char('p') => {
+                self.show_practice_plan();
+            }
+            Key::Char('o') => {
+                self.state = AppState::Profile;
+            }
+            Key::Char('g') => {
+                self.state = AppState::Goals;
+            }
+            Key::Char('r') => {
+                self.resume_session();
+            }
+            Key::Char('v') => {
+                self.replay_last_session();
+            }
+            Key::Char('d') => {
+                self.start_session_with_seed(lesson_of_the_day_seed(
+                    Clock::now().naive_utc().date(),
+                ));
+            }
+            Key::Char('h') => {
+                self.lesson_list
+                    .set_ghost(Ghost::next(self.lesson_list.ghost()));
+            }
+            Key::Char('s') => {
+                self.seed_input = Some(String::new());
+            }
+            Key::Char('e') => {
+                self.state = AppState::LessonEditor;
             }
             _ => {}
         }
     }
 
+    /// Starts the selected lesson with the typed seed, e.g. one shown in the
+    /// results of another session.
+    fn handle_seed_input(&mut self, input_key: Key) {
+        let seed_input = match self.seed_input.as_mut() {
+            Some(seed_input) => seed_input,
+            None => return,
+        };
+        match input_key {
+            Key::Esc => self.seed_input = None,
+            Key::Backspace => {
+                seed_input.pop();
+            }
+            Key::Char(c) if c.is_ascii_digit() => seed_input.push(c),
+            Key::Enter => match seed_input.parse::<u64>() {
+                Ok(seed) if self.lesson_list.current_lesson().is_some() => {
+                    self.seed_input = None;
+                    self.start_session_with_seed(seed);
+                }
+                Ok(_) => self.notify("Select a lesson to type the text of the seed"),
+                Err(_) => self.notify(format!("The seed has to be a number up to {}", u64::MAX)),
+            },
+            _ => {}
+        }
+    }
+
+    fn handle_lesson_editor(&mut self, input_key: Key) {
+        if let Some(draft) = self.lesson_draft.as_mut() {
+            match input_key {
+                Key::Esc => self.lesson_draft = None,
+                Key::Ctrl('s') => self.save_lesson_draft(),
+                // Enter picks a key in the keys field
+                Key::Enter if draft.field != DraftField::Keys => self.save_lesson_draft(),
+                _ => draft.handle_key(input_key, self.lesson_list.layout()),
+            }
+            return;
+        }
+        let pending_deletion = self.pending_deletion.take();
+        let lesson_count = self.lessons().len();
+        let selected_lesson = self
+            .lesson_list
+            .selected_index()
+            .filter(|index| *index < lesson_count);
+        match (input_key, selected_lesson) {
+            (Key::Esc, _) => {
+                self.state = AppState::LessonSelection;
+                self.save_progress();
+            }
+            (Key::Down, _) => {
+                self.lesson_list.select_next_lesson();
+            }
+            (Key::Up, _) => {
+                self.lesson_list.select_prev_lesson();
+            }
+            (Key::Char('n'), _) => {
+                self.lesson_draft = Some(LessonDraft::new());
+            }
+            (Key::Enter, Some(index)) | (Key::Char('e'), Some(index)) => {
+                self.lesson_draft = Some(LessonDraft::edit(index, &self.lessons()[index]));
+            }
+            (Key::Char('c'), Some(index)) => {
+                self.lesson_draft = Some(LessonDraft::derived_from(index, &self.lessons()[index]));
+            }
+            (Key::Char('y'), Some(index)) => {
+                let lesson = &self.lessons()[index];
+                let copy = lesson
+                    .clone()
+                    .with_name(format!("{} (copy)", lesson.name()));
+                self.insert_lesson(index + 1, copy);
+            }
+            (Key::Char('['), Some(index)) if index > 0 => {
+                self.lesson_list.swap_lessons(index - 1, index);
+                self.lessons_changed();
+            }
+            (Key::Char(']'), Some(index)) if index + 1 < lesson_count => {
+                self.lesson_list.swap_lessons(index, index + 1);
+                self.lessons_changed();
+            }
+            (Key::Char('x'), Some(index)) if pending_deletion == Some(index) => {
+                self.lesson_list.remove_lesson(index);
+                self.lessons_changed();
+            }
+            (Key::Char('x'), Some(index)) => {
+                self.pending_deletion = Some(index);
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds or replaces the lesson of the draft, the draft stays open if it
+    /// does not describe a valid lesson.
+    fn save_lesson_draft(&mut self) {
+        let (origin, lesson) = match self.lesson_draft.as_ref() {
+            Some(draft) => (draft.origin, draft.build(self.lessons())),
+            None => return,
+        };
+        let lesson = match lesson {
+            Ok(lesson) => lesson,
+            Err(message) => return self.notify(message),
+        };
+        match origin {
+            DraftOrigin::New => self.insert_lesson(self.lessons().len(), lesson),
+            DraftOrigin::DerivedFrom(index) => self.insert_lesson(index + 1, lesson),
+            DraftOrigin::Edit(index) => self.lesson_list.replace_lesson(index, lesson),
+        }
+        self.lesson_draft = None;
+    }
+
+    fn insert_lesson(&mut self, index: usize, lesson: Lesson) {
+        self.lesson_list.insert_lesson(index, lesson);
+        self.lesson_list.select_lesson(index);
+        self.lessons_changed();
+    }
+
+    /// Lesson indices kept outside of the lesson list are no longer valid
+    /// once lessons moved.
+    fn lessons_changed(&mut self) {
+        self.suspended_session = None;
+        self.practice_plan.clear();
+        self.planned_session = None;
+    }
+
     pub fn start_session(&mut self) {
+        self.start_session_with_seed(rand::random());
+    }
+
+    /// Starts the selected lesson with the content generated from `seed`.
+    fn start_session_with_seed(&mut self, seed: u64) {
+        if self.lesson_list.is_current_lesson_locked() {
+            return;
+        }
         if let Some(lesson) = self.lesson_list.current_lesson() {
-            self.lesson_progress = TrainingSession::new(lesson.generate_lesson_content());
-            self.state = AppState::Training;
+            let session = self.create_session(lesson, seed);
+            self.begin_training(session);
+        }
+    }
+
+    fn create_session(&self, lesson: &Lesson, seed: u64) -> TrainingSession {
+        self.session_with_content(lesson, lesson.generate_lesson_content_with_seed(seed))
+            .with_seed(Some(seed))
+    }
+
+    /// The lesson the current session belongs to, a drill, the lesson of
+    /// the routine step or the selected lesson.
+    fn session_lesson(&self) -> Option<&Lesson> {
+        match &self.planned_session {
+            Some(PlannedSession::WeakKeyDrill { lesson }) => Some(lesson),
+            _ => self.lesson_list.lessons().get(self.session_lesson_index()?),
+        }
+    }
+
+    /// Index of the lesson the current session is recorded for, None for
+    /// drills.
+    fn session_lesson_index(&self) -> Option<usize> {
+        if let Some(PlannedSession::WeakKeyDrill { .. }) = self.planned_session {
+            return None;
+        }
+        match &self.routine_run {
+            Some(routine_run) => routine_run.lesson_index,
+            None => self
+                .lesson_list
+                .selected_index()
+                .filter(|index| *index < self.lesson_list.lessons().len()),
+        }
+    }
+
+    fn session_with_content(&self, lesson: &Lesson, content: String) -> TrainingSession {
+        TrainingSession::new(content)
+            .with_compose_sequences(lesson.compose_sequences())
+            .with_layout(self.lesson_list.layout())
+            .with_idle_timeout(self.lesson_list.idle_timeout())
+    }
+
+    /// Starts training with a new session, an earlier suspended one is
+    /// discarded. The ghost races at the pace set for the lesson.
+    fn begin_training(&mut self, session: TrainingSession) {
+        let ghost_speed = self.lesson_list.ghost_speed(self.session_lesson_index());
+        self.lesson_progress = session.with_ghost(ghost_speed);
+        self.suspended_session = None;
+        self.state = AppState::Training;
+    }
+
+    /// Plans today's practice and shows it if there is anything to do.
+    pub fn show_practice_plan(&mut self) {
+        self.practice_plan = plan_practice(&self.lesson_list, Clock::now()).into();
+        if !self.practice_plan.is_empty() {
+            self.state = AppState::Planner;
+        }
+    }
+
+    /// Starts the next session of the practice plan, returns to the lesson
+    /// selection once all planned sessions are done.
+    fn start_next_planned_session(&mut self) {
+        let planned_session = match self.practice_plan.pop_front() {
+            Some(planned_session) => planned_session,
+            None => {
+                self.state = AppState::LessonSelection;
+                return;
+            }
+        };
+        self.state = AppState::Planner;
+        match &planned_session {
+            PlannedSession::Review { lesson_index, .. }
+            | PlannedSession::NewLesson { lesson_index } => {
+                self.lesson_list.select_lesson(*lesson_index);
+                self.start_session();
+            }
+            PlannedSession::WeakKeyDrill { lesson } => {
+                let session = self.create_session(lesson, rand::random());
+                self.begin_training(session);
+            }
+        }
+        if self.state == AppState::Training {
+            self.planned_session = Some(planned_session);
+        } else {
+            // the lesson got locked in the meantime
+            self.start_next_planned_session();
         }
     }
 }
 
 #[cfg(test)]
-mod test_lesson {}
+mod test_lesson {
+    use chrono::Duration;
+    use pretty_assertions::assert_eq;
+
+    use crate::core::routine::StepLimit;
+    use crate::core::weighting_strategy::WeightingStrategy;
+
+    use super::*;
+
+    fn press(app: &mut TrainerApp, keys: &[Key]) {
+        for key in keys {
+            app.tick(OptionalInput::InputKey(InputEvent::new(*key, Clock::now())));
+        }
+    }
+
+    fn type_text(app: &mut TrainerApp, text: &str) {
+        for c in text.chars() {
+            press(app, &[Key::Char(c)]);
+        }
+    }
+
+    fn lesson_names(app: &TrainerApp) -> Vec<&str> {
+        app.lessons().iter().map(Lesson::name).collect()
+    }
+
+    #[test]
+    fn routine_steps_are_recorded_for_their_lesson_and_end_on_input() {
+        let lesson_1 = Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        );
+        let lesson_2 = lesson_1.add_chars(
+            String::from("Lesson 2"),
+            &['b'],
+            WeightingStrategy::EqualWeight,
+        );
+        let mut app = TrainerApp::new(Vec::new());
+        app.lesson_list =
+            SelectableLessonList::new(vec![lesson_1, lesson_2]).with_routines(vec![Routine::new(
+                "Routine",
+            )
+            .then(
+                StepSource::Lesson { lesson_index: 1 },
+                StepLimit::Seconds(1),
+            )]);
+        app.lesson_list.select_lesson_entry(2);
+        press(&mut app, &[Key::Enter]);
+        assert_eq!(app.state(), &AppState::Training);
+        let first_key = app.lesson_progress.lesson_content.chars().next().unwrap();
+        press(&mut app, &[Key::Char(first_key)]);
+        Clock::advance(Duration::seconds(2));
+        press(&mut app, &[Key::Char(first_key)]);
+
+        assert_eq!(app.state(), &AppState::SessionSummary);
+        assert!(app.routine_summary().is_some());
+        assert!(app.lesson_list.lesson_records(0).is_empty());
+        assert_eq!(app.lesson_list.lesson_records(1).len(), 1);
+        assert_eq!(app.lesson_list.selected_index(), Some(2));
+        press(&mut app, &[Key::Char('t')]);
+        assert!(app.notification().is_some());
+        assert_eq!(app.state(), &AppState::SessionSummary)
+    }
+
+    #[test]
+    fn a_wrong_key_is_highlighted_until_the_next_tick() {
+        let mut app = TrainerApp::new(vec![Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        )]);
+        press(&mut app, &[Key::Down, Key::Enter, Key::Char('x')]);
+        assert_eq!(app.lesson_progress.last_wrong_input(), Some('x'));
+        app.tick(OptionalInput::NoInput);
+        assert_eq!(app.lesson_progress.last_wrong_input(), None)
+    }
+
+    #[test]
+    fn the_key_after_an_idle_pause_only_resumes_the_session() {
+        let mut app = TrainerApp::new(vec![Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        )]);
+        press(&mut app, &[Key::Down, Key::Enter]);
+        let content: Vec<char> = app.lesson_progress.lesson_content.chars().collect();
+        press(&mut app, &[Key::Char(content[0])]);
+
+        // paused by a tick
+        Clock::advance(Duration::seconds(20));
+        app.tick(OptionalInput::NoInput);
+        assert!(app.lesson_progress.is_paused());
+        press(&mut app, &[Key::Char(content[1])]);
+        app.tick(OptionalInput::NoInput);
+        assert!(!app.lesson_progress.is_paused());
+        assert_eq!(app.lesson_progress.stats().typed_characters, 1);
+
+        // idle without a tick in between
+        Clock::advance(Duration::seconds(20));
+        press(&mut app, &[Key::Char(content[1])]);
+        assert!(!app.lesson_progress.is_paused());
+        assert_eq!(app.lesson_progress.stats().typed_characters, 1);
+        press(&mut app, &[Key::Char(content[1])]);
+        assert_eq!(app.lesson_progress.stats().typed_characters, 2)
+    }
+
+    #[test]
+    fn an_idle_pause_ends_at_the_time_of_the_resuming_key() {
+        let mut app = TrainerApp::new(vec![Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        )]);
+        press(&mut app, &[Key::Down, Key::Enter]);
+        let content: Vec<char> = app.lesson_progress.lesson_content.chars().collect();
+        let start = Clock::now();
+        press(&mut app, &[Key::Char(content[0])]);
+
+        // the key was pressed 15 seconds in, but handled 5 seconds later
+        Clock::advance(Duration::seconds(20));
+        let key_time = start + Duration::seconds(15);
+        app.tick(OptionalInput::InputKey(InputEvent::new(
+            Key::Char(content[1]),
+            key_time,
+        )));
+        assert!(!app.lesson_progress.is_paused());
+        assert_eq!(app.lesson_progress.duration(), Duration::seconds(5))
+    }
+
+    #[test]
+    fn an_invalid_save_file_is_reported_and_left_untouched() {
+        let file_path = std::env::temp_dir().join(format!("corrupt-{}.json", std::process::id()));
+        std::fs::write(&file_path, "{\"lessons\": 3").unwrap();
+        let app = TrainerApp::load(&file_path);
+        app.save().unwrap();
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+
+        assert!(app
+            .notification()
+            .unwrap()
+            .contains("is not a valid save file"));
+        assert!(app.lessons().is_empty());
+        assert_eq!(app.state(), &AppState::LessonSelection);
+        assert_eq!(content, "{\"lessons\": 3")
+    }
+
+    #[test]
+    fn a_replayed_session_has_the_statistics_of_its_record() {
+        let mut app = TrainerApp::new(vec![Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        )]);
+        press(&mut app, &[Key::Down, Key::Enter]);
+        let content = app.lesson_progress.lesson_content.clone();
+        for (index, c) in content.chars().enumerate() {
+            Clock::advance(Duration::milliseconds(150 + 50 * (index as i64 % 3)));
+            if index == 2 {
+                press(&mut app, &[Key::Char('x')]);
+            }
+            press(&mut app, &[Key::Char(c)]);
+        }
+        assert_eq!(app.state(), &AppState::SessionSummary);
+
+        press(&mut app, &[Key::Char('v')]);
+        assert_eq!(app.state(), &AppState::Replay);
+        Clock::advance(Duration::seconds(60));
+        app.tick(OptionalInput::NoInput);
+        let replay = app.replay().unwrap();
+        assert!(replay.is_finished());
+        let record = &app.lesson_list.lesson_records(0)[0];
+        let replayed = replay.session().stats();
+        assert_eq!(replayed.errors, record.stats.errors);
+        assert_eq!(replayed.errors.total_error_count, 1);
+        assert_eq!(replayed.typed_characters, record.stats.typed_characters);
+        assert_eq!(replayed.duration_seconds, record.stats.duration_seconds);
+        assert_eq!(Some(replay.session().recording()), record.recording)
+    }
+
+    #[test]
+    fn the_text_of_a_seed_is_typed_again_and_the_seed_is_recorded() {
+        let lesson = Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b', 'c'],
+            10,
+            3,
+            WeightingStrategy::EqualWeight,
+        );
+        let expected_content = lesson.generate_lesson_content_with_seed(42);
+        let mut app = TrainerApp::new(vec![lesson]);
+        press(&mut app, &[Key::Down, Key::Char('s')]);
+        type_text(&mut app, "42x");
+        press(&mut app, &[Key::Enter]);
+        assert_eq!(app.lesson_progress.lesson_content, expected_content);
+
+        type_text(&mut app, &expected_content);
+        assert_eq!(app.state(), &AppState::SessionSummary);
+        assert_eq!(app.session_summary().unwrap().seed, Some(42));
+        assert_eq!(app.lesson_list.lesson_records(0)[0].seed, Some(42));
+        press(&mut app, &[Key::Char('t')]);
+        assert_eq!(app.state(), &AppState::Training);
+        assert_eq!(app.lesson_progress.lesson_content, expected_content);
+        assert_eq!(app.lesson_progress.seed(), Some(42))
+    }
+
+    #[test]
+    fn lessons_are_derived_moved_and_deleted_in_the_editor() {
+        let mut app = TrainerApp::new(vec![Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        )]);
+        press(&mut app, &[Key::Down, Key::Char('e'), Key::Char('c')]);
+        assert_eq!(app.state(), &AppState::LessonEditor);
+        press(&mut app, &[Key::Enter]);
+        assert_eq!(app.notification(), Some("The lesson needs a name"));
+        type_text(&mut app, "Lesson 2");
+        press(&mut app, &[Key::Tab]);
+        type_text(&mut app, "c");
+        press(&mut app, &[Key::Ctrl('s')]);
+        assert_eq!(app.lesson_draft(), None);
+        assert_eq!(lesson_names(&app), vec!["Lesson 1", "Lesson 2"]);
+        assert_eq!(app.lessons()[1].keys().len(), 3);
+        assert_eq!(app.lesson_list.selected_index(), Some(1));
+
+        press(&mut app, &[Key::Char('y'), Key::Char('[')]);
+        assert_eq!(
+            lesson_names(&app),
+            vec!["Lesson 1", "Lesson 2 (copy)", "Lesson 2"]
+        );
+        press(&mut app, &[Key::Char('x'), Key::Down, Key::Char('x')]);
+        assert_eq!(app.lessons().len(), 3);
+        press(&mut app, &[Key::Char('x'), Key::Char('x'), Key::Esc]);
+        assert_eq!(lesson_names(&app), vec!["Lesson 1", "Lesson 2 (copy)"]);
+        assert_eq!(app.state(), &AppState::LessonSelection)
+    }
+}
@@ -1,12 +1,18 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::iter::FromIterator;
 
 use chrono::Duration;
 use chrono::{DateTime, Utc};
-use tui::style::{Color, Style};
-use tui::text::{Span, Spans, Text};
+use unicode_segmentation::UnicodeSegmentation;
 
-use crate::core::stats::{TrainingRecord, TrainingStatistics};
+use crate::core::compose::{match_input, ComposeSequence, SequenceMatch};
+use crate::core::enums::Key;
+use crate::core::ghost::ghost_position;
+use crate::core::input::InputEvent;
+use crate::core::layout::{KeyboardLayout, Layer};
+use crate::core::stats::{
+    Keystroke, LayerStatistics, Recording, TrainingRecord, TrainingStatistics,
+};
 use crate::core::typing_errors::TypingErrors;
 use crate::core::typing_speed::TypingSpeed;
 #[cfg(not(test))]
@@ -14,6 +20,24 @@ use crate::wrapper::clock::Clock;
 #[cfg(test)]
 use crate::wrapper::fake_clock::FakeClock as Clock;
 
+/// How a part of the lesson content is shown.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SegmentState {
+    Done,
+    Current,
+    /// The current grapheme after a wrong input.
+    Wrong,
+    Pending,
+    /// The grapheme the ghost is at, unless it is at the current one.
+    Ghost,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Segment {
+    pub text: String,
+    pub state: SegmentState,
+}
+
 enum InputResult {
     None,
     Wrong,
@@ -21,120 +45,495 @@ enum InputResult {
 }
 pub struct TrainingSession {
     pub lesson_content: String,
-    finished_chars: Vec<char>,
-    remaining_chars: VecDeque<char>,
-    current_char: Option<char>,
+    finished_chars: Vec<String>,
+    remaining_chars: VecDeque<String>,
+    current_char: Option<String>,
+    pending_input: String,
     last_input_result: InputResult,
+    last_wrong_input: Option<char>,
     start_time: Option<DateTime<Utc>>,
     end_time: Option<DateTime<Utc>>,
     errors: u16,
+    compose_sequences: Vec<ComposeSequence>,
+    sequence_step_errors: BTreeMap<String, Vec<u16>>,
+    errors_by_key: BTreeMap<String, u16>,
+    key_times: BTreeMap<String, Vec<Duration>>,
+    last_completion_time: Option<DateTime<Utc>>,
+    layout: Option<KeyboardLayout>,
+    layer_statistics: BTreeMap<Layer, LayerStatistics>,
+    last_input_time: Option<DateTime<Utc>>,
+    idle_timeout: Option<Duration>,
+    paused_since: Option<DateTime<Utc>>,
+    paused_duration: Duration,
+    time_limit: Option<Duration>,
+    keystrokes: Vec<Keystroke>,
+    ghost_speed: Option<TypingSpeed>,
+    seed: Option<u64>,
+}
+
+impl Default for TrainingSession {
+    fn default() -> Self {
+        TrainingSession::new(String::from(" "))
+    }
 }
 
 impl TrainingSession {
     pub fn new(lesson: String) -> TrainingSession {
-        let mut remaining_chars = VecDeque::from_iter(lesson.chars());
-        let finished_chars: Vec<char> = Vec::with_capacity(remaining_chars.len());
+        let mut remaining_chars: VecDeque<String> =
+            lesson.graphemes(true).map(String::from).collect();
+        let finished_chars: Vec<String> = Vec::with_capacity(remaining_chars.len());
         let next_char = remaining_chars.pop_front();
         TrainingSession {
             lesson_content: lesson,
             finished_chars,
             remaining_chars,
             current_char: next_char,
+            pending_input: String::new(),
             last_input_result: InputResult::None,
+            last_wrong_input: None,
             start_time: None,
             end_time: None,
             errors: 0,
+            compose_sequences: Vec::new(),
+            sequence_step_errors: BTreeMap::new(),
+            errors_by_key: BTreeMap::new(),
+            key_times: BTreeMap::new(),
+            last_completion_time: None,
+            layout: None,
+            layer_statistics: BTreeMap::new(),
+            last_input_time: None,
+            idle_timeout: None,
+            paused_since: None,
+            paused_duration: Duration::zero(),
+            time_limit: None,
+            keystrokes: Vec::new(),
+            ghost_speed: None,
+            seed: None,
         }
     }
 
-    pub fn default() -> Self {
-        TrainingSession::new(String::from(" "))
+    /// Additionally accepts the raw dead key or compose sequences for the
+    /// characters they produce.
+    pub fn with_compose_sequences(mut self, compose_sequences: &[ComposeSequence]) -> Self {
+        self.compose_sequences = compose_sequences.to_vec();
+        self
+    }
+
+    /// Uses the layout to break the statistics down by layer and to tell
+    /// wrong keys apart from wrong modifiers.
+    pub fn with_layout(mut self, layout: Option<&KeyboardLayout>) -> Self {
+        self.layout = layout.cloned();
+        self
+    }
+
+    /// Pauses the session once no key was pressed for `idle_timeout`. The
+    /// pause starts with the last keystroke, so the idle time is not counted.
+    pub fn with_idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Finishes the session once it took `time_limit`, even if not all of
+    /// the content was typed.
+    pub fn with_time_limit(mut self, time_limit: Option<Duration>) -> Self {
+        self.time_limit = time_limit;
+        self
+    }
+
+    /// Races a ghost typing at `ghost_speed` through the content.
+    pub fn with_ghost(mut self, ghost_speed: Option<TypingSpeed>) -> Self {
+        self.ghost_speed = ghost_speed;
+        self
+    }
+
+    /// Remembers the seed the content was generated from.
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
     }
 
+    /// Feeds one typed character into the session. Graphemes that consist of
+    /// several code points or are reached through a compose sequence are only
+    /// completed once all of their steps were typed.
     pub fn handle_key(&mut self, current_input: char) {
-        if self.start_time == None {
+        self.handle_key_at(current_input, Clock::now());
+    }
+
+    /// Types the character of a key event at the time of the event, other
+    /// keys are left to the caller.
+    pub fn handle_event(&mut self, event: &InputEvent) {
+        if let Key::Char(c) = event.key {
+            self.handle_key_at(c, event.timestamp);
+        }
+    }
+
+    /// Like `handle_key` for a key that was pressed at `typed_at`, which is
+    /// used for all timing instead of the time the key is handled.
+    pub fn handle_key_at(&mut self, current_input: char, typed_at: DateTime<Utc>) {
+        if self.current_char.is_none() || self.is_time_up_at(typed_at) {
+            return;
+        }
+        if self.start_time.is_none() {
             if current_input == ' ' {
                 return;
             }
-            self.start_time = Some(Clock::now());
+            self.start_time = Some(typed_at);
         }
-        if current_input == self.current_char.unwrap() {
-            self.finished_chars.push(current_input);
-            self.current_char = self.remaining_chars.pop_front();
-            self.last_input_result = InputResult::Correct;
-        } else {
-            self.errors += 1;
-            self.last_input_result = InputResult::Wrong;
+        self.pause_if_idle_at(typed_at);
+        self.resume_at(typed_at);
+        self.last_input_time = Some(typed_at);
+        self.keystrokes.push(Keystroke {
+            offset_milliseconds: self.duration_until(typed_at).num_milliseconds() as u32,
+            key: current_input,
+        });
+        let current_char = match &self.current_char {
+            Some(current_char) => current_char.clone(),
+            None => return,
+        };
+        self.pending_input.push(current_input);
+        match match_input(&self.pending_input, &current_char, &self.compose_sequences) {
+            SequenceMatch::Complete => {
+                self.layer_statistics_for(&current_char).typed_characters += 1;
+                self.record_key_time(&current_char, typed_at);
+                self.pending_input.clear();
+                self.finished_chars.push(current_char);
+                self.current_char = self.remaining_chars.pop_front();
+                self.last_input_result = InputResult::Correct;
+                self.last_wrong_input = None;
+            }
+            SequenceMatch::Incomplete => {}
+            SequenceMatch::WrongStep(step) => {
+                self.record_sequence_step_error(current_char.clone(), step);
+                self.record_error(current_char, current_input);
+            }
+            SequenceMatch::Wrong => {
+                self.record_layer_error(&current_char, current_input);
+                self.record_error(current_char, current_input);
+            }
+        }
+        if self.current_char.is_none() || self.is_time_up_at(typed_at) {
+            self.end_time = Some(typed_at);
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.start_time.is_some()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_since.is_some()
+    }
+
+    /// Stops the clock until the session is resumed. Sessions that were not
+    /// started yet or are already finished have no clock running.
+    pub fn pause(&mut self) {
+        if self.is_started() && !self.is_finished() && !self.is_paused() {
+            self.paused_since = Some(Clock::now());
+        }
+    }
+
+    /// Pauses the session retroactively from the last keystroke if the idle
+    /// timeout has passed since then.
+    pub fn pause_if_idle(&mut self) {
+        self.pause_if_idle_at(Clock::now());
+    }
+
+    pub fn pause_if_idle_at(&mut self, now: DateTime<Utc>) {
+        if self.is_paused() || self.is_finished() {
+            return;
+        }
+        if let (Some(last_input_time), Some(idle_timeout)) =
+            (self.last_input_time, self.idle_timeout)
+        {
+            if now - last_input_time > idle_timeout {
+                self.paused_since = Some(last_input_time);
+            }
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.resume_at(Clock::now());
+    }
+
+    /// Ends the pause at `now`, e.g. the time of the key that resumes it.
+    pub fn resume_at(&mut self, now: DateTime<Utc>) {
+        if let Some(paused_since) = self.paused_since.take() {
+            let pause = now - paused_since;
+            self.paused_duration = self.paused_duration + pause;
+            // the pause does not count as time spent on the next key
+            self.last_completion_time = self
+                .last_completion_time
+                .map(|last_completion_time| last_completion_time + pause);
+            // nor as idle time, which would pause the session right away
+            self.last_input_time = self
+                .last_input_time
+                .map(|last_input_time| last_input_time + pause);
+        }
+    }
+
+    /// Keeps the time since the previous character was completed. The first
+    /// character has none as the clock starts with its keystroke.
+    fn record_key_time(&mut self, target: &str, now: DateTime<Utc>) {
+        if let Some(last_completion_time) = self.last_completion_time {
+            self.key_times
+                .entry(target.to_string())
+                .or_default()
+                .push(now - last_completion_time);
+        }
+        self.last_completion_time = Some(now);
+    }
+
+    fn record_error(&mut self, target: String, wrong_input: char) {
+        self.pending_input.clear();
+        self.errors += 1;
+        *self.errors_by_key.entry(target).or_default() += 1;
+        self.last_input_result = InputResult::Wrong;
+        self.last_wrong_input = Some(wrong_input);
+    }
+
+    fn layer_statistics_for(&mut self, target: &str) -> &mut LayerStatistics {
+        let layer = match &self.layout {
+            Some(layout) => layout.layer_of(target),
+            None => Layer::Base,
+        };
+        self.layer_statistics.entry(layer).or_default()
+    }
+
+    /// An error counts as a modifier error if the right key was hit on the
+    /// wrong layer. Otherwise the key itself was wrong, and if the layer was
+    /// wrong too it counts as both.
+    fn record_layer_error(&mut self, target: &str, typed: char) {
+        let (target_position, target_layer) =
+            match self.layout.as_ref().and_then(|l| l.find(target)) {
+                Some(found) => found,
+                None => {
+                    self.layer_statistics_for(target).base_key_errors += 1;
+                    return;
+                }
+            };
+        let typed_key = self
+            .layout
+            .as_ref()
+            .and_then(|layout| layout.find(&typed.to_string()));
+        let statistics = self.layer_statistics.entry(target_layer).or_default();
+        match typed_key {
+            Some((typed_position, typed_layer)) => {
+                if typed_position != target_position {
+                    statistics.base_key_errors += 1;
+                }
+                if typed_layer != target_layer {
+                    statistics.modifier_errors += 1;
+                }
+            }
+            None => statistics.base_key_errors += 1,
         }
-        if self.is_finished() {
-            self.end_time = Some(Clock::now());
+    }
+
+    fn record_sequence_step_error(&mut self, target: String, step: usize) {
+        let step_errors = self.sequence_step_errors.entry(target).or_default();
+        if step_errors.len() <= step {
+            step_errors.resize(step + 1, 0);
         }
+        step_errors[step] += 1;
     }
 
-    pub fn get_diff(&self) -> Text {
-        const ZERO_WIDTH_CHAR: char = '\u{200B}';
-        Text {
-            lines: vec![Spans::from(vec![
-                Span::styled(
-                    String::from_iter(&self.finished_chars),
-                    Style::default().fg(Color::Green),
-                ),
-                Span::styled(
-                    self.current_char.unwrap_or(ZERO_WIDTH_CHAR).to_string(),
-                    Style::default().fg(match self.last_input_result {
-                        InputResult::None | InputResult::Correct => Color::Gray,
-                        InputResult::Wrong => Color::Red,
-                    }),
-                ),
-                Span::styled(
-                    String::from_iter(&self.remaining_chars),
-                    Style::default().fg(Color::DarkGray),
-                ),
-            ])],
+    /// The lesson content split into what was typed, the grapheme to type
+    /// next and the rest. The current segment is empty once finished.
+    pub fn segments(&self) -> Vec<Segment> {
+        let current_state = match self.last_input_result {
+            InputResult::None | InputResult::Correct => SegmentState::Current,
+            InputResult::Wrong => SegmentState::Wrong,
+        };
+        let finished_count = self.finished_chars.len();
+        let remaining: Vec<String> = self.remaining_chars.iter().cloned().collect();
+        let ghost_position = self.ghost_position();
+        let mut segments = Vec::new();
+        match ghost_position {
+            Some(ghost) if ghost < finished_count => segments.extend(split_at_ghost(
+                &self.finished_chars,
+                ghost,
+                SegmentState::Done,
+            )),
+            _ => segments.push(Segment {
+                text: self.finished_chars.concat(),
+                state: SegmentState::Done,
+            }),
         }
+        segments.push(Segment {
+            text: self.current_char.clone().unwrap_or_default(),
+            state: current_state,
+        });
+        match ghost_position {
+            Some(ghost) if ghost > finished_count && ghost - finished_count <= remaining.len() => {
+                let ghost_in_remaining = ghost - finished_count - 1;
+                segments.extend(split_at_ghost(
+                    &remaining,
+                    ghost_in_remaining,
+                    SegmentState::Pending,
+                ))
+            }
+            _ => segments.push(Segment {
+                text: String::from_iter(self.remaining_chars.iter().map(String::as_str)),
+                state: SegmentState::Pending,
+            }),
+        }
+        segments
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn ghost_speed(&self) -> Option<TypingSpeed> {
+        self.ghost_speed
+    }
+
+    /// Index of the grapheme the ghost has reached, at most the length of
+    /// the content once the ghost is through. The ghost starts with the
+    /// first keystroke and waits during pauses.
+    pub fn ghost_position(&self) -> Option<usize> {
+        let position = ghost_position(self.ghost_speed?, self.duration());
+        Some(position.min(self.lesson_content.graphemes(true).count()))
+    }
+
+    /// Graphemes typed more than the ghost, negative when behind it.
+    pub fn ghost_lead(&self) -> Option<i64> {
+        Some(self.finished_chars.len() as i64 - self.ghost_position()? as i64)
+    }
+
+    /// The grapheme that has to be typed next.
+    pub fn current_char(&self) -> Option<&str> {
+        self.current_char.as_deref()
+    }
+
+    /// The last typed character if it was wrong, until the next correct input
+    /// or until it is cleared.
+    pub fn last_wrong_input(&self) -> Option<char> {
+        self.last_wrong_input
+    }
+
+    /// Forgets the last wrong input, e.g. once it was shown briefly.
+    pub fn clear_wrong_input(&mut self) {
+        self.last_wrong_input = None;
+    }
+
+    /// Average time it took to type each key, slowest first.
+    pub fn slowest_keys(&self) -> Vec<(String, Duration)> {
+        let mut average_key_times: Vec<(String, Duration)> = self
+            .key_times
+            .iter()
+            .map(|(key, times)| {
+                let total = times.iter().fold(Duration::zero(), |sum, time| sum + *time);
+                (key.clone(), total / times.len() as i32)
+            })
+            .collect();
+        average_key_times.sort_by_key(|(_, average)| std::cmp::Reverse(*average));
+        average_key_times
     }
 
     pub fn is_finished(&self) -> bool {
-        self.current_char.is_none()
+        self.current_char.is_none() || self.is_time_up()
+    }
+
+    fn is_time_up(&self) -> bool {
+        self.is_time_up_at(Clock::now())
+    }
+
+    fn is_time_up_at(&self, now: DateTime<Utc>) -> bool {
+        match self.time_limit {
+            Some(time_limit) => self.duration_until(now) >= time_limit,
+            None => false,
+        }
+    }
+
+    /// Time from the first keystroke until the session was finished or now,
+    /// without the time the session was paused.
+    pub fn duration(&self) -> Duration {
+        self.duration_until(Clock::now())
+    }
+
+    fn duration_until(&self, now: DateTime<Utc>) -> Duration {
+        let start_time = match self.start_time {
+            Some(start_time) => start_time,
+            None => return Duration::zero(),
+        };
+        let end_time = self.end_time.or(self.paused_since).unwrap_or(now);
+        let duration = end_time - start_time - self.paused_duration;
+        match self.time_limit {
+            Some(time_limit) => duration.min(time_limit),
+            None => duration,
+        }
     }
 
     pub fn typing_speed(&self) -> TypingSpeed {
-        if let Some(start_time) = self.start_time {
-            let duration: Duration = self.end_time.unwrap_or(Clock::now()) - start_time;
-            if duration < Duration::seconds(1) {
-                TypingSpeed::CharactersPerMinute(0)
-            } else {
-                let characters_per_minute =
-                    60 * self.finished_chars.len() as u32 / duration.num_seconds() as u32;
-                TypingSpeed::CharactersPerMinute(characters_per_minute as u16)
-            }
-        } else {
+        let duration = self.duration();
+        if duration < Duration::seconds(1) {
             TypingSpeed::CharactersPerMinute(0)
+        } else {
+            let characters_per_minute =
+                60 * self.finished_chars.len() as u32 / duration.num_seconds() as u32;
+            TypingSpeed::CharactersPerMinute(characters_per_minute as u16)
         }
     }
 
     pub fn progress(&self) -> f64 {
-        self.finished_chars.len() as f64 / self.lesson_content.len() as f64
+        if let Some(time_limit) = self.time_limit {
+            return self.duration().num_milliseconds() as f64
+                / time_limit.num_milliseconds() as f64;
+        }
+        self.finished_chars.len() as f64 / self.lesson_content.graphemes(true).count() as f64
     }
 
     pub fn errors(&self) -> TypingErrors {
         TypingErrors {
             total_error_count: self.errors,
+            sequence_step_errors: self.sequence_step_errors.clone(),
+            errors_by_key: self.errors_by_key.clone(),
         }
     }
     pub fn stats(&self) -> TrainingStatistics {
         TrainingStatistics {
             errors: self.errors(),
             typing_speed: self.typing_speed(),
+            layers: self.layer_statistics.clone(),
+            typed_characters: self.finished_chars.len() as u32,
+            duration_seconds: self.duration().num_seconds() as u32,
         }
     }
     pub fn training_record(&self) -> TrainingRecord {
-        TrainingRecord {
-            timestamp: self.start_time.unwrap_or(Utc::now()),
-            stats: self.stats(),
+        TrainingRecord::new(self.start_time.unwrap_or(Utc::now()), self.stats())
+            .with_recording(self.recording())
+            .with_seed(self.seed)
+    }
+    /// The typed keys with their timing, to be played back by a `Replay`.
+    pub fn recording(&self) -> Recording {
+        Recording {
+            content: self.lesson_content.clone(),
+            keystrokes: self.keystrokes.clone(),
+            time_limit_seconds: self
+                .time_limit
+                .map(|time_limit| time_limit.num_seconds() as u32),
         }
     }
 }
+/// The graphemes as three segments around the one at `ghost`.
+fn split_at_ghost(graphemes: &[String], ghost: usize, state: SegmentState) -> Vec<Segment> {
+    vec![
+        Segment {
+            text: graphemes[..ghost].concat(),
+            state,
+        },
+        Segment {
+            text: graphemes[ghost].clone(),
+            state: SegmentState::Ghost,
+        },
+        Segment {
+            text: graphemes[ghost + 1..].concat(),
+            state,
+        },
+    ]
+}
+
 #[cfg(test)]
 mod test_training_session {
     use std::ops::Add;
@@ -145,20 +544,22 @@ mod test_training_session {
 
     use super::*;
 
+    fn graphemes<T: FromIterator<String>>(content: &str) -> T {
+        content.graphemes(true).map(String::from).collect()
+    }
+
     #[test]
     fn test_characters_per_minute_also_counts_spaces() {
         let now = Clock::now();
         let in_1_minute = now.add(Duration::minutes(1));
         let content_with_15_chars = "abcde fghijklmn";
         let unit = TrainingSession {
-            lesson_content: content_with_15_chars.to_string(),
-            finished_chars: Vec::from_iter(content_with_15_chars.chars()),
+            finished_chars: graphemes(content_with_15_chars),
             remaining_chars: VecDeque::new(),
             current_char: None,
-            last_input_result: InputResult::Correct,
             start_time: Some(now),
             end_time: Some(in_1_minute),
-            errors: 0,
+            ..TrainingSession::new(content_with_15_chars.to_string())
         };
         assert_eq!(unit.typing_speed(), TypingSpeed::CharactersPerMinute(15))
     }
@@ -166,14 +567,10 @@ mod test_training_session {
     fn test_characters_per_minute_is_undefined_if_not_started() {
         let content_with_15_chars = "abcde fghijklmn";
         let unit = TrainingSession {
-            lesson_content: content_with_15_chars.to_string(),
-            finished_chars: Vec::from_iter(content_with_15_chars.chars()),
+            finished_chars: graphemes(content_with_15_chars),
             remaining_chars: VecDeque::new(),
             current_char: None,
-            last_input_result: InputResult::Correct,
-            start_time: None,
-            end_time: None,
-            errors: 0,
+            ..TrainingSession::new(content_with_15_chars.to_string())
         };
         assert_eq!(unit.typing_speed(), TypingSpeed::CharactersPerMinute(0))
     }
@@ -183,15 +580,294 @@ mod test_training_session {
         FakeClock::advance(Duration::minutes(1));
         let content_with_15_chars = "abcde fghijklmn";
         let unit = TrainingSession {
-            lesson_content: content_with_15_chars.to_string(),
-            finished_chars: Vec::from_iter("abcde".chars()),
-            remaining_chars: VecDeque::from_iter(" fghijklmn".chars()),
+            finished_chars: graphemes("abcde"),
+            remaining_chars: graphemes(" fghijklmn"),
             current_char: None,
-            last_input_result: InputResult::Correct,
             start_time: Some(now),
-            end_time: None,
-            errors: 0,
+            ..TrainingSession::new(content_with_15_chars.to_string())
         };
         assert_eq!(unit.typing_speed(), TypingSpeed::CharactersPerMinute(5))
     }
+
+    #[test]
+    fn test_progress_counts_multi_byte_characters_once() {
+        let mut unit = TrainingSession::new(String::from("äöüß"));
+        unit.handle_key('ä');
+        unit.handle_key('ö');
+        assert_eq!(unit.progress(), 0.5)
+    }
+    #[test]
+    fn test_combining_character_is_finished_after_all_code_points() {
+        let mut unit = TrainingSession::new(String::from("e\u{301}a"));
+        unit.handle_key('e');
+        assert_eq!(unit.progress(), 0.0);
+        unit.handle_key('\u{301}');
+        assert_eq!(unit.progress(), 0.5);
+        assert_eq!(unit.errors().total_error_count, 0)
+    }
+    #[test]
+    fn test_wrong_code_point_within_combining_character_is_an_error() {
+        let mut unit = TrainingSession::new(String::from("e\u{301}a"));
+        unit.handle_key('e');
+        unit.handle_key('a');
+        assert_eq!(unit.progress(), 0.0);
+        assert_eq!(unit.errors().total_error_count, 1)
+    }
+    #[test]
+    fn test_typing_speed_counts_graphemes() {
+        let content_with_15_chars = "äöüßé fghijklmn";
+        let mut unit = TrainingSession::new(content_with_15_chars.to_string());
+        for c in content_with_15_chars.chars() {
+            unit.handle_key(c);
+            FakeClock::advance(Duration::seconds(4));
+        }
+        assert!(unit.is_finished());
+        assert_eq!(unit.typing_speed(), TypingSpeed::CharactersPerMinute(16))
+    }
+    #[test]
+    fn test_composed_input_is_accepted_for_dead_key_character() {
+        let mut unit = TrainingSession::new(String::from("éa"))
+            .with_compose_sequences(&[ComposeSequence::dead_key('´', 'e', 'é')]);
+        unit.handle_key('é');
+        assert_eq!(unit.progress(), 0.5)
+    }
+    #[test]
+    fn test_raw_dead_key_sequence_is_accepted() {
+        let mut unit = TrainingSession::new(String::from("éa"))
+            .with_compose_sequences(&[ComposeSequence::dead_key('´', 'e', 'é')]);
+        unit.handle_key('´');
+        assert_eq!(unit.progress(), 0.0);
+        unit.handle_key('e');
+        assert_eq!(unit.progress(), 0.5);
+        assert_eq!(unit.errors().total_error_count, 0)
+    }
+    #[test]
+    fn test_errors_are_attributed_to_the_wrong_sequence_step() {
+        let mut unit = TrainingSession::new(String::from("éa"))
+            .with_compose_sequences(&[ComposeSequence::dead_key('´', 'e', 'é')]);
+        unit.handle_key('´');
+        unit.handle_key('a');
+        unit.handle_key('`');
+        unit.handle_key('´');
+        unit.handle_key('e');
+        let errors = unit.errors();
+        assert_eq!(errors.total_error_count, 2);
+        assert_eq!(errors.sequence_step_errors.get("é"), Some(&vec![1, 1]));
+        assert_eq!(unit.progress(), 0.5)
+    }
+    #[test]
+    fn test_wrong_modifier_on_right_key_is_a_modifier_error() {
+        let bone = KeyboardLayout::bone();
+        let mut unit = TrainingSession::new(String::from("Ei")).with_layout(Some(&bone));
+        unit.handle_key('e');
+        unit.handle_key('E');
+        let shift_stats = unit.stats().layers.get(&Layer::Shift).cloned();
+        assert_eq!(
+            shift_stats,
+            Some(LayerStatistics {
+                typed_characters: 1,
+                base_key_errors: 0,
+                modifier_errors: 1
+            })
+        )
+    }
+    #[test]
+    fn test_wrong_key_on_right_layer_is_a_base_key_error() {
+        let bone = KeyboardLayout::bone();
+        let mut unit = TrainingSession::new(String::from("ei")).with_layout(Some(&bone));
+        unit.handle_key('i');
+        unit.handle_key('e');
+        let base_stats = unit.stats().layers.get(&Layer::Base).cloned();
+        assert_eq!(
+            base_stats,
+            Some(LayerStatistics {
+                typed_characters: 1,
+                base_key_errors: 1,
+                modifier_errors: 0
+            })
+        )
+    }
+    #[test]
+    fn test_wrong_key_and_layer_counts_as_both() {
+        let bone = KeyboardLayout::bone();
+        let mut unit = TrainingSession::new(String::from("{")).with_layout(Some(&bone));
+        unit.handle_key('a');
+        let mod3_stats = unit.stats().layers.get(&Layer::Mod3).cloned();
+        assert_eq!(
+            mod3_stats,
+            Some(LayerStatistics {
+                typed_characters: 0,
+                base_key_errors: 1,
+                modifier_errors: 1
+            })
+        )
+    }
+    #[test]
+    fn test_errors_are_counted_per_expected_key() {
+        let mut unit = TrainingSession::new(String::from("ab"));
+        unit.handle_key('x');
+        unit.handle_key('a');
+        unit.handle_key('a');
+        unit.handle_key('c');
+        let errors_by_key = unit.errors().errors_by_key;
+        assert_eq!(errors_by_key.get("a"), Some(&1));
+        assert_eq!(errors_by_key.get("b"), Some(&2))
+    }
+    #[test]
+    fn test_slowest_keys_are_sorted_by_average_time() {
+        let mut unit = TrainingSession::new(String::from("abcb"));
+        unit.handle_key('a');
+        FakeClock::advance(Duration::milliseconds(200));
+        unit.handle_key('b');
+        FakeClock::advance(Duration::milliseconds(500));
+        unit.handle_key('c');
+        FakeClock::advance(Duration::milliseconds(400));
+        unit.handle_key('b');
+        assert_eq!(
+            unit.slowest_keys(),
+            vec![
+                (String::from("c"), Duration::milliseconds(500)),
+                (String::from("b"), Duration::milliseconds(300))
+            ]
+        )
+    }
+    #[test]
+    fn test_last_wrong_input_is_kept_until_next_correct_input() {
+        let mut unit = TrainingSession::new(String::from("ab"));
+        unit.handle_key('x');
+        assert_eq!(unit.last_wrong_input(), Some('x'));
+        unit.handle_key('a');
+        assert_eq!(unit.last_wrong_input(), None);
+        assert_eq!(unit.current_char(), Some("b"))
+    }
+    #[test]
+    fn test_paused_time_is_not_part_of_the_duration() {
+        let mut unit = TrainingSession::new(String::from("abc"));
+        unit.handle_key('a');
+        FakeClock::advance(Duration::seconds(10));
+        unit.pause();
+        FakeClock::advance(Duration::minutes(5));
+        assert_eq!(unit.duration(), Duration::seconds(10));
+        unit.handle_key('b');
+        FakeClock::advance(Duration::seconds(10));
+        unit.handle_key('c');
+        assert!(!unit.is_paused());
+        assert_eq!(unit.duration(), Duration::seconds(20));
+        assert_eq!(
+            unit.slowest_keys(),
+            vec![
+                (String::from("b"), Duration::seconds(10)),
+                (String::from("c"), Duration::seconds(10))
+            ]
+        )
+    }
+    #[test]
+    fn test_idle_time_is_excluded_retroactively() {
+        let mut unit = TrainingSession::new(String::from("abc"))
+            .with_idle_timeout(Some(Duration::seconds(30)));
+        unit.handle_key('a');
+        FakeClock::advance(Duration::seconds(20));
+        unit.handle_key('b');
+        FakeClock::advance(Duration::minutes(2));
+        unit.pause_if_idle();
+        assert!(unit.is_paused());
+        assert_eq!(unit.duration(), Duration::seconds(20));
+        unit.handle_key('c');
+        assert_eq!(unit.duration(), Duration::seconds(20))
+    }
+    #[test]
+    fn test_unstarted_session_can_not_be_paused() {
+        let mut unit = TrainingSession::new(String::from("abc"));
+        unit.pause();
+        assert!(!unit.is_paused())
+    }
+    #[test]
+    fn test_time_limited_session_ignores_keys_after_the_limit() {
+        let mut unit =
+            TrainingSession::new(String::from("abcd")).with_time_limit(Some(Duration::minutes(1)));
+        unit.handle_key('a');
+        FakeClock::advance(Duration::seconds(30));
+        unit.handle_key('b');
+        assert_eq!(unit.progress(), 0.5);
+        FakeClock::advance(Duration::seconds(40));
+        assert!(unit.is_finished());
+        unit.handle_key('c');
+        assert_eq!(unit.duration(), Duration::minutes(1));
+        assert_eq!(unit.typing_speed(), TypingSpeed::CharactersPerMinute(2))
+    }
+    #[test]
+    fn test_segments_show_wrong_input_on_current_grapheme() {
+        let mut unit = TrainingSession::new(String::from("abc"));
+        unit.handle_key('a');
+        unit.handle_key('x');
+        assert_eq!(
+            unit.segments(),
+            vec![
+                Segment {
+                    text: String::from("a"),
+                    state: SegmentState::Done
+                },
+                Segment {
+                    text: String::from("b"),
+                    state: SegmentState::Wrong
+                },
+                Segment {
+                    text: String::from("c"),
+                    state: SegmentState::Pending
+                },
+            ]
+        )
+    }
+    #[test]
+    fn test_ghost_advances_with_the_session_time() {
+        // 600 characters per minute, one every 100 ms
+        let mut unit = TrainingSession::new(String::from("abcdef"))
+            .with_ghost(Some(TypingSpeed::CharactersPerMinute(600)));
+        assert_eq!(unit.ghost_position(), Some(0));
+        unit.handle_key('a');
+        FakeClock::advance(Duration::milliseconds(350));
+        assert_eq!(unit.ghost_position(), Some(3));
+        assert_eq!(unit.ghost_lead(), Some(-2));
+        let texts: Vec<(String, SegmentState)> = unit
+            .segments()
+            .into_iter()
+            .map(|segment| (segment.text, segment.state))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (String::from("a"), SegmentState::Done),
+                (String::from("b"), SegmentState::Current),
+                (String::from("c"), SegmentState::Pending),
+                (String::from("d"), SegmentState::Ghost),
+                (String::from("ef"), SegmentState::Pending),
+            ]
+        );
+        unit.handle_key('b');
+        unit.handle_key('c');
+        unit.handle_key('d');
+        unit.handle_key('e');
+        assert_eq!(unit.ghost_lead(), Some(2));
+        assert_eq!(unit.segments()[1].state, SegmentState::Ghost);
+        FakeClock::advance(Duration::seconds(10));
+        assert_eq!(unit.ghost_position(), Some(6));
+        assert_eq!(unit.segments().len(), 3)
+    }
+    #[test]
+    fn test_timing_uses_the_time_keys_were_typed_at() {
+        let mut unit = TrainingSession::new(String::from("abc"));
+        let start = FakeClock::now();
+        unit.handle_key_at('a', start);
+        unit.handle_key_at('b', start + Duration::milliseconds(300));
+        unit.handle_key_at('c', start + Duration::milliseconds(500));
+        FakeClock::advance(Duration::seconds(5));
+        assert_eq!(unit.duration(), Duration::milliseconds(500));
+        assert_eq!(
+            unit.slowest_keys(),
+            vec![
+                (String::from("b"), Duration::milliseconds(300)),
+                (String::from("c"), Duration::milliseconds(200))
+            ]
+        )
+    }
 }
@@ -1,13 +1,13 @@
-use std::fs::OpenOptions;
-
-use crate::app::selectable_session_list::SelectableLessonList;
-use crate::app::trainer::TrainerApp;
-use crate::core::lesson::Lesson;
-use crate::core::weighting_strategy::WeightingStrategy;
-
-mod app;
-mod core;
-mod wrapper;
+use keyboard_layout_lessons::app::persistence::save_lesson_list;
+use keyboard_layout_lessons::app::selectable_session_list::SelectableLessonList;
+use keyboard_layout_lessons::app::trainer::TrainerApp;
+use keyboard_layout_lessons::core::character::Character;
+use keyboard_layout_lessons::core::compose::ComposeSequence;
+use keyboard_layout_lessons::core::layout::{KeyboardLayout, Layer};
+use keyboard_layout_lessons::core::lesson::Lesson;
+use keyboard_layout_lessons::core::mastery::MasteryCriteria;
+use keyboard_layout_lessons::core::routine::{Routine, StepLimit, StepSource};
+use keyboard_layout_lessons::core::weighting_strategy::WeightingStrategy;
 
 fn create_bone_home_row_lessons() -> Vec<Lesson> {
     let lesson_length = 80;
@@ -54,20 +54,93 @@ fn create_bone_home_row_lessons() -> Vec<Lesson> {
 
     lessons
 }
+// capitals first, then the Mod3 symbols and the Mod4 number block of the home row
+fn create_bone_layer_lessons(base_lesson: &Lesson, layout: &KeyboardLayout) -> Vec<Lesson> {
+    let capitals_1 = base_lesson.add_characters(
+        String::from("Capitals 1"),
+        &layout.characters(&['I', 'E', 'N', 'R']),
+        WeightingStrategy::EqualWeight,
+    );
+    let capitals_2 = capitals_1.add_characters(
+        String::from("Capitals 2"),
+        &layout.characters(&['T', 'S', 'C', 'G', 'O', 'B']),
+        WeightingStrategy::EqualWeight,
+    );
+    let symbols_1 = capitals_2.add_characters(
+        String::from("Symbols 1"),
+        &layout.characters(&['(', ')', '-', ':']),
+        WeightingStrategy::EqualWeight,
+    );
+    let symbols_2 = symbols_1.add_characters(
+        String::from("Symbols 2"),
+        &layout.characters(&['/', '{', '}', '*']),
+        WeightingStrategy::EqualWeight,
+    );
+    let numbers = symbols_2.add_characters(
+        String::from("Numbers 1"),
+        // the number row has the digits on the base layer too
+        &['4', '5', '6']
+            .iter()
+            .map(|c| Character::on_layer(*c, Layer::Mod4))
+            .collect::<Vec<Character>>(),
+        WeightingStrategy::EqualWeight,
+    );
+    vec![capitals_1, capitals_2, symbols_1, symbols_2, numbers]
+}
+
+// dead keys for accents are placed right of ß (´) and in the top left (^)
+fn create_bone_dead_key_lessons(base_lesson: &Lesson) -> Vec<Lesson> {
+    let acute = base_lesson.add_compose_sequences(
+        String::from("Acute accent"),
+        &[ComposeSequence::dead_key('´', 'e', 'é')],
+        WeightingStrategy::EqualWeight,
+    );
+    let circumflex = acute.add_compose_sequences(
+        String::from("Circumflex"),
+        &[
+            ComposeSequence::dead_key('^', 'e', 'ê'),
+            ComposeSequence::dead_key('^', 'o', 'ô'),
+        ],
+        WeightingStrategy::EqualWeight,
+    );
+    vec![acute, circumflex]
+}
+
+/// Warms up on the first home row lesson, drills the weak keys and ends with
+/// the whole home row.
+fn create_bone_routines(lessons: &[Lesson]) -> Vec<Routine> {
+    let lesson_step = |name: &str| StepSource::Lesson {
+        lesson_index: lessons
+            .iter()
+            .position(|lesson| lesson.name() == name)
+            .expect("the routine refers to a missing lesson"),
+    };
+    vec![Routine::new("Warm-up")
+        .then(lesson_step("Lesson 1"), StepLimit::Seconds(120))
+        .then(StepSource::WeakKeys, StepLimit::Seconds(300))
+        .then(lesson_step("Lesson 5"), StepLimit::Seconds(180))]
+}
+
 pub fn create_bone_trainer() -> TrainerApp {
     let home_row_lessons = self::create_bone_home_row_lessons();
     TrainerApp::new(home_row_lessons)
 }
 
 fn main() -> anyhow::Result<()> {
-    let lessons = self::create_bone_home_row_lessons();
-    let data = SelectableLessonList::new(lessons);
+    let layout = KeyboardLayout::bone();
+    let mut lessons = self::create_bone_home_row_lessons();
+    let home_row_lesson = lessons.last().unwrap().clone();
+    lessons.extend(self::create_bone_layer_lessons(&home_row_lesson, &layout));
+    lessons.extend(self::create_bone_dead_key_lessons(&home_row_lesson));
+    let routines = self::create_bone_routines(&lessons);
+    let data = SelectableLessonList::new(lessons)
+        .with_routines(routines)
+        .with_layout(layout)
+        .with_mastery_criteria(MasteryCriteria {
+            min_net_words_per_minute: 25,
+            min_accuracy: 0.95,
+            session_count: 3,
+        });
 
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open("save.json")?;
-    serde_json::to_writer(file, &data)?;
-    Ok(())
+    save_lesson_list(&data, "save.json")
 }
@@ -0,0 +1,192 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::core::stats::TrainingRecord;
+
+const MILESTONE_WORDS_PER_MINUTE: u16 = 40;
+const MILESTONE_STREAK_DAYS: u32 = 7;
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum AchievementKind {
+    /// First session with 40 WPM on a layout.
+    FortyWordsPerMinute,
+    /// A session without any error.
+    PerfectAccuracy,
+    /// Practiced on seven days in a row.
+    SevenDayStreak,
+}
+
+impl AchievementKind {
+    pub fn description(&self) -> &'static str {
+        match self {
+            AchievementKind::FortyWordsPerMinute => "First 40 WPM",
+            AchievementKind::PerfectAccuracy => "100% accuracy session",
+            AchievementKind::SevenDayStreak => "7 day streak",
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct Achievement {
+    pub kind: AchievementKind,
+    /// Layout the achievement was earned on, if it is specific to one.
+    pub layout: Option<String>,
+    pub achieved_at: DateTime<Utc>,
+}
+
+/// Days in a row with at least one session. The current streak is still
+/// alive if the last session was yesterday. Days are counted in UTC.
+#[derive(PartialEq, Debug, Default, Clone, Copy)]
+pub struct PracticeStreak {
+    pub current_days: u32,
+    pub longest_days: u32,
+}
+
+pub fn practice_streak<'a>(
+    timestamps: impl Iterator<Item = &'a DateTime<Utc>>,
+    today: NaiveDate,
+) -> PracticeStreak {
+    let practice_days: BTreeSet<NaiveDate> =
+        timestamps.map(|time| time.naive_utc().date()).collect();
+    let mut streak = PracticeStreak::default();
+    let mut running_days = 0;
+    let mut previous_day: Option<NaiveDate> = None;
+    for day in practice_days.iter() {
+        running_days = match previous_day {
+            Some(previous_day) if *day - previous_day == Duration::days(1) => running_days + 1,
+            _ => 1,
+        };
+        streak.longest_days = streak.longest_days.max(running_days);
+        previous_day = Some(*day);
+    }
+    if let Some(last_day) = previous_day {
+        if today - last_day <= Duration::days(1) {
+            streak.current_days = running_days;
+        }
+    }
+    streak
+}
+
+/// Achievements earned so far, kept in the save file.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Profile {
+    pub achievements: Vec<Achievement>,
+}
+
+impl Profile {
+    fn has_achievement(&self, kind: AchievementKind, layout: Option<&str>) -> bool {
+        self.achievements
+            .iter()
+            .any(|achievement| achievement.kind == kind && achievement.layout.as_deref() == layout)
+    }
+
+    /// Checks a new session for milestones and returns the ones reached for
+    /// the first time.
+    pub fn record_session(
+        &mut self,
+        record: &TrainingRecord,
+        streak: PracticeStreak,
+        layout: Option<&str>,
+    ) -> Vec<Achievement> {
+        let mut earned: Vec<(AchievementKind, Option<&str>)> = Vec::new();
+        if record.stats.typing_speed.words_per_minute() >= MILESTONE_WORDS_PER_MINUTE {
+            earned.push((AchievementKind::FortyWordsPerMinute, layout));
+        }
+        if record.stats.typed_characters > 0 && record.stats.errors.total_error_count == 0 {
+            earned.push((AchievementKind::PerfectAccuracy, None));
+        }
+        if streak.current_days >= MILESTONE_STREAK_DAYS {
+            earned.push((AchievementKind::SevenDayStreak, None));
+        }
+        let mut new_achievements = Vec::new();
+        for (kind, layout) in earned {
+            if !self.has_achievement(kind, layout) {
+                let achievement = Achievement {
+                    kind,
+                    layout: layout.map(String::from),
+                    achieved_at: record.timestamp,
+                };
+                self.achievements.push(achievement.clone());
+                new_achievements.push(achievement);
+            }
+        }
+        new_achievements
+    }
+}
+
+#[cfg(test)]
+mod test_achievements {
+    use pretty_assertions::assert_eq;
+
+    use crate::core::stats::record;
+
+    use super::*;
+
+    fn day(day: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2021-03-{:02}T12:00:00Z", day))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn streak_counts_consecutive_days() {
+        let timestamps = [day(1), day(2), day(2), day(3), day(5), day(6)];
+        let streak = practice_streak(timestamps.iter(), day(6).naive_utc().date());
+        assert_eq!(
+            streak,
+            PracticeStreak {
+                current_days: 2,
+                longest_days: 3
+            }
+        )
+    }
+    #[test]
+    fn streak_is_alive_until_a_day_is_missed() {
+        let timestamps = [day(1), day(2)];
+        assert_eq!(
+            practice_streak(timestamps.iter(), day(3).naive_utc().date()).current_days,
+            2
+        );
+        assert_eq!(
+            practice_streak(timestamps.iter(), day(4).naive_utc().date()).current_days,
+            0
+        )
+    }
+    #[test]
+    fn milestones_are_earned_once() {
+        let mut profile = Profile::default();
+        let earned =
+            profile.record_session(&record(45, 0), PracticeStreak::default(), Some("Bone"));
+        let kinds: Vec<AchievementKind> = earned.iter().map(|a| a.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                AchievementKind::FortyWordsPerMinute,
+                AchievementKind::PerfectAccuracy
+            ]
+        );
+        let earned =
+            profile.record_session(&record(45, 0), PracticeStreak::default(), Some("Bone"));
+        assert_eq!(earned, Vec::new())
+    }
+    #[test]
+    fn speed_milestone_is_earned_per_layout() {
+        let mut profile = Profile::default();
+        profile.record_session(&record(45, 1), PracticeStreak::default(), Some("Bone"));
+        let earned = profile.record_session(&record(45, 1), PracticeStreak::default(), Some("Neo"));
+        assert_eq!(earned.len(), 1);
+        assert_eq!(earned[0].layout, Some(String::from("Neo")))
+    }
+    #[test]
+    fn seven_day_streak_is_a_milestone() {
+        let mut profile = Profile::default();
+        let streak = PracticeStreak {
+            current_days: 7,
+            longest_days: 7,
+        };
+        let earned = profile.record_session(&record(10, 1), streak, None);
+        assert_eq!(earned[0].kind, AchievementKind::SevenDayStreak)
+    }
+}
@@ -2,13 +2,33 @@ use std::clone::Clone;
 
 use serde::{Deserialize, Serialize};
 
+use crate::core::layout::Layer;
+
+/// A single key of a lesson, stored as one grapheme cluster so that
+/// characters built from several code points (e.g. `e` + combining acute)
+/// are treated as one unit. `layer` tells which modifiers are needed to type
+/// it on the layout the lesson was made for.
 #[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
 pub struct Character {
-    pub value: char,
+    pub value: String,
+    #[serde(default)]
+    pub layer: Layer,
 }
 
 impl Character {
     pub fn new(value: char) -> Character {
-        Character { value }
+        Character::on_layer(value, Layer::Base)
+    }
+    pub fn on_layer(value: char, layer: Layer) -> Character {
+        Character {
+            value: value.to_string(),
+            layer,
+        }
+    }
+    pub fn from_grapheme(value: &str) -> Character {
+        Character {
+            value: value.to_string(),
+            layer: Layer::Base,
+        }
     }
 }
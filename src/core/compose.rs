@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+/// Describes how a character is reached through a dead key or compose
+/// sequence, e.g. `´` followed by `e` for `é`.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct ComposeSequence {
+    pub result: String,
+    pub steps: Vec<char>,
+}
+
+impl ComposeSequence {
+    pub fn new(result: &str, steps: &[char]) -> ComposeSequence {
+        ComposeSequence {
+            result: result.to_string(),
+            steps: steps.to_vec(),
+        }
+    }
+    pub fn dead_key(dead_key: char, base: char, result: char) -> ComposeSequence {
+        ComposeSequence::new(&result.to_string(), &[dead_key, base])
+    }
+    pub fn raw_input(&self) -> String {
+        self.steps.iter().collect()
+    }
+}
+
+/// Outcome of matching the input typed so far against a target character.
+#[derive(PartialEq, Debug)]
+pub enum SequenceMatch {
+    Complete,
+    Incomplete,
+    /// The input went wrong at the given step of a compose sequence.
+    WrongStep(usize),
+    Wrong,
+}
+
+/// Matches `input` against `target`, which can either be typed directly or
+/// through one of the given compose sequences producing it.
+pub fn match_input(input: &str, target: &str, sequences: &[ComposeSequence]) -> SequenceMatch {
+    let raw_sequences: Vec<String> = sequences
+        .iter()
+        .filter(|sequence| sequence.result == target)
+        .map(ComposeSequence::raw_input)
+        .collect();
+    if input == target || raw_sequences.iter().any(|raw| raw == input) {
+        return SequenceMatch::Complete;
+    }
+    if target.starts_with(input) || raw_sequences.iter().any(|raw| raw.starts_with(input)) {
+        return SequenceMatch::Incomplete;
+    }
+    if raw_sequences.is_empty() {
+        SequenceMatch::Wrong
+    } else {
+        SequenceMatch::WrongStep(input.chars().count() - 1)
+    }
+}
+
+#[cfg(test)]
+mod test_compose {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn acute_e() -> Vec<ComposeSequence> {
+        vec![ComposeSequence::dead_key('´', 'e', 'é')]
+    }
+
+    #[test]
+    fn composed_input_completes_target() {
+        assert_eq!(match_input("é", "é", &acute_e()), SequenceMatch::Complete)
+    }
+    #[test]
+    fn raw_sequence_completes_target() {
+        assert_eq!(match_input("´e", "é", &acute_e()), SequenceMatch::Complete)
+    }
+    #[test]
+    fn dead_key_alone_is_incomplete() {
+        assert_eq!(match_input("´", "é", &acute_e()), SequenceMatch::Incomplete)
+    }
+    #[test]
+    fn wrong_base_key_is_attributed_to_second_step() {
+        assert_eq!(
+            match_input("´a", "é", &acute_e()),
+            SequenceMatch::WrongStep(1)
+        )
+    }
+    #[test]
+    fn wrong_dead_key_is_attributed_to_first_step() {
+        assert_eq!(
+            match_input("`", "é", &acute_e()),
+            SequenceMatch::WrongStep(0)
+        )
+    }
+    #[test]
+    fn sequences_for_other_characters_are_ignored() {
+        assert_eq!(match_input("´e", "a", &acute_e()), SequenceMatch::Wrong)
+    }
+}
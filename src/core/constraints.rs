@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::core::layout::{Finger, KeyboardLayout};
+
+/// Optional rules for the generated words of a lesson, to leave out words
+/// like "iiii" that waste practice time. They are best-effort: keys that
+/// would break the run or same-finger rule are not drawn while other keys
+/// are left, and words breaking a rule are drawn again a limited number of
+/// times. Rules the keys of a lesson cannot satisfy, e.g. distinct words
+/// from two keys, are followed as far as possible.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GenerationConstraints {
+    /// Longest run of the same character within a word.
+    #[serde(default)]
+    pub max_repeated_run: Option<u8>,
+    #[serde(default)]
+    pub same_finger_bigrams: Option<SameFingerBigrams>,
+    /// Share of the characters of each word, from 0 to 1, that are keys
+    /// introduced by the lesson.
+    #[serde(default)]
+    pub min_new_key_share: Option<f64>,
+    /// A word is not repeated within this many following words.
+    #[serde(default)]
+    pub distinct_words: Option<usize>,
+}
+
+/// Limits the pairs of different characters in a word that are typed one
+/// after the other with the same finger.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct SameFingerBigrams {
+    /// Name of the built-in layout the fingers are taken from.
+    pub layout: String,
+    pub max_per_word: u8,
+}
+
+/// The constraints of a lesson with the layout and the new keys resolved,
+/// ready to check generated words.
+pub struct WordRules<'a> {
+    constraints: &'a GenerationConstraints,
+    layout: Option<KeyboardLayout>,
+    new_keys: Vec<&'a str>,
+}
+
+impl<'a> WordRules<'a> {
+    /// Rules for words of a lesson that introduces `new_keys`. Without new
+    /// keys the minimum share of them is not enforced.
+    pub fn new(constraints: &'a GenerationConstraints, new_keys: Vec<&'a str>) -> WordRules<'a> {
+        let layout = constraints
+            .same_finger_bigrams
+            .as_ref()
+            .and_then(|bigrams| KeyboardLayout::by_name(&bigrams.layout));
+        WordRules {
+            constraints,
+            layout,
+            new_keys,
+        }
+    }
+
+    pub fn is_new_key(&self, key: &str) -> bool {
+        self.new_keys.contains(&key)
+    }
+
+    /// How many characters of a word with `length` characters have to be
+    /// new keys.
+    pub fn required_new_keys(&self, length: usize) -> usize {
+        match self.constraints.min_new_key_share {
+            Some(share) if !self.new_keys.is_empty() => {
+                ((share.clamp(0.0, 1.0) * length as f64).ceil() as usize).min(length)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Whether `next` may follow the characters of a word drawn so far.
+    pub fn allows_next(&self, drawn: &[&str], next: &str) -> bool {
+        if let Some(max_run) = self.constraints.max_repeated_run {
+            let run = drawn.iter().rev().take_while(|key| **key == next).count();
+            if run >= max_run.max(1) as usize {
+                return false;
+            }
+        }
+        if let (Some(bigrams), Some(layout), Some(last)) = (
+            &self.constraints.same_finger_bigrams,
+            &self.layout,
+            drawn.last(),
+        ) {
+            if is_same_finger_bigram(layout, last, next)
+                && same_finger_bigrams(layout, drawn) >= bigrams.max_per_word as usize
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether a complete word follows all rules, `previous_words` being the
+    /// words generated before it, the latest last.
+    pub fn accepts(&self, word: &str, previous_words: &[String]) -> bool {
+        let graphemes: Vec<&str> = word.graphemes(true).collect();
+        if let Some(max_run) = self.constraints.max_repeated_run {
+            if longest_run(&graphemes) > max_run.max(1) as usize {
+                return false;
+            }
+        }
+        if let (Some(bigrams), Some(layout)) = (&self.constraints.same_finger_bigrams, &self.layout)
+        {
+            if same_finger_bigrams(layout, &graphemes) > bigrams.max_per_word as usize {
+                return false;
+            }
+        }
+        let new_keys = graphemes.iter().filter(|key| self.is_new_key(key)).count();
+        if new_keys < self.required_new_keys(graphemes.len()) {
+            return false;
+        }
+        if let Some(distinct_words) = self.constraints.distinct_words {
+            let recent = &previous_words[previous_words.len().saturating_sub(distinct_words)..];
+            if recent.iter().any(|previous| previous == word) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Length of the longest run of the same grapheme.
+pub fn longest_run(graphemes: &[&str]) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    for (index, grapheme) in graphemes.iter().enumerate() {
+        if index > 0 && graphemes[index - 1] == *grapheme {
+            run += 1;
+        } else {
+            run = 1;
+        }
+        longest = longest.max(run);
+    }
+    longest
+}
+
+fn finger(layout: &KeyboardLayout, grapheme: &str) -> Option<Finger> {
+    layout.find(grapheme).map(|(position, _)| position.finger())
+}
+
+/// Whether two different characters are typed with the same finger.
+/// Characters missing on the layout are never counted.
+pub fn is_same_finger_bigram(layout: &KeyboardLayout, first: &str, second: &str) -> bool {
+    first != second
+        && match (finger(layout, first), finger(layout, second)) {
+            (Some(first), Some(second)) => first == second,
+            _ => false,
+        }
+}
+
+pub fn same_finger_bigrams(layout: &KeyboardLayout, graphemes: &[&str]) -> usize {
+    graphemes
+        .windows(2)
+        .filter(|pair| is_same_finger_bigram(layout, pair[0], pair[1]))
+        .count()
+}
+
+#[cfg(test)]
+mod test_constraints {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn runs_and_same_finger_bigrams_are_counted() {
+        assert_eq!(longest_run(&["a", "b", "b", "b", "a", "a"]), 3);
+        assert_eq!(longest_run(&[]), 0);
+        let bone = KeyboardLayout::bone();
+        // "c" and "j" are both typed with the left pinky on Bone
+        assert_eq!(same_finger_bigrams(&bone, &["c", "j", "c", "c", "e"]), 2);
+        assert!(!is_same_finger_bigram(&bone, "c", "€"));
+    }
+
+    #[test]
+    fn rules_are_only_checked_if_set() {
+        let constraints = GenerationConstraints::default();
+        let rules = WordRules::new(&constraints, vec!["a"]);
+        assert!(rules.allows_next(&["a", "a", "a"], "a"));
+        assert!(rules.accepts("aaaa", &[String::from("aaaa")]));
+        assert_eq!(rules.required_new_keys(4), 0);
+    }
+}
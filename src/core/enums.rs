@@ -1,13 +1,42 @@
-use termion::event::Key;
+use crate::core::input::InputEvent;
+
+/// A key press independent of the terminal backend. Enter and Tab are keys
+/// of their own, `Char` only holds printable characters.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Key {
+    Char(char),
+    Ctrl(char),
+    Alt(char),
+    Enter,
+    Tab,
+    Backspace,
+    Esc,
+    Up,
+    Down,
+    Left,
+    Right,
+    Other,
+}
 
 pub enum OptionalInput {
-    InputKey(Key),
+    InputKey(InputEvent),
+    /// The terminal changed its size, the next draw adapts to it.
+    Resize,
+    /// A tick of the event loop or an event the app does not handle.
     NoInput,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Debug)]
 pub enum AppState {
+    Planner,
     LessonSelection,
     Training,
+    /// A recorded session is played back in the training panel.
+    Replay,
+    SessionSummary,
+    Profile,
+    Goals,
+    /// Lessons are created, edited, reordered and deleted.
+    LessonEditor,
     Terminated,
 }
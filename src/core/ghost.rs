@@ -0,0 +1,85 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::core::typing_speed::TypingSpeed;
+
+/// A pace to race against while training, shown as a second cursor in the
+/// lesson text.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum Ghost {
+    /// The fastest earlier session on the lesson.
+    PersonalBest,
+    TargetWordsPerMinute(u16),
+}
+
+impl Ghost {
+    pub const DEFAULT_TARGET_WORDS_PER_MINUTE: u16 = 40;
+
+    /// Cycles through no ghost, the personal best and a target speed.
+    pub fn next(ghost: Option<Ghost>) -> Option<Ghost> {
+        match ghost {
+            None => Some(Ghost::PersonalBest),
+            Some(Ghost::PersonalBest) => Some(Ghost::TargetWordsPerMinute(
+                Ghost::DEFAULT_TARGET_WORDS_PER_MINUTE,
+            )),
+            Some(Ghost::TargetWordsPerMinute(_)) => None,
+        }
+    }
+
+    pub fn describe(ghost: Option<Ghost>) -> String {
+        match ghost {
+            None => String::from("off"),
+            Some(Ghost::PersonalBest) => String::from("personal best"),
+            Some(Ghost::TargetWordsPerMinute(words_per_minute)) => {
+                format!("{} WPM", words_per_minute)
+            }
+        }
+    }
+}
+
+/// Number of graphemes a ghost typing at `speed` has typed after `elapsed`.
+pub fn ghost_position(speed: TypingSpeed, elapsed: Duration) -> usize {
+    (speed.characters_per_minute() as i64 * elapsed.num_milliseconds() / 60_000).max(0) as usize
+}
+
+/// How far ahead of the ghost (positive) or behind it (negative) `lead`
+/// graphemes are, also in seconds at the pace of the ghost.
+pub fn describe_lead(lead: i64, speed: TypingSpeed) -> String {
+    let seconds = match speed.characters_per_minute() {
+        0 => 0.0,
+        characters_per_minute => lead.abs() as f64 * 60.0 / characters_per_minute as f64,
+    };
+    match lead {
+        0 => String::from("level with the ghost"),
+        lead if lead > 0 => format!("{} ahead of the ghost ({:.1} s)", lead, seconds),
+        lead => format!("{} behind the ghost ({:.1} s)", -lead, seconds),
+    }
+}
+
+#[cfg(test)]
+mod test_ghost {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn ghost_advances_with_its_speed() {
+        let speed = TypingSpeed::WordsPerMinute(60);
+        assert_eq!(ghost_position(speed, Duration::zero()), 0);
+        assert_eq!(ghost_position(speed, Duration::milliseconds(900)), 4);
+        assert_eq!(ghost_position(speed, Duration::seconds(10)), 50)
+    }
+    #[test]
+    fn lead_is_described_in_graphemes_and_seconds() {
+        let speed = TypingSpeed::CharactersPerMinute(300);
+        assert_eq!(describe_lead(0, speed), "level with the ghost");
+        assert_eq!(describe_lead(3, speed), "3 ahead of the ghost (0.6 s)");
+        assert_eq!(describe_lead(-10, speed), "10 behind the ghost (2.0 s)")
+    }
+    #[test]
+    fn ghosts_are_cycled() {
+        let first = Ghost::next(None);
+        assert_eq!(first, Some(Ghost::PersonalBest));
+        assert_eq!(Ghost::next(Ghost::next(first)), None)
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::core::stats::TrainingRecord;
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum DailyGoal {
+    Minutes(u32),
+    Sessions(u32),
+    Characters(u32),
+}
+
+/// Practice accumulated over all lessons on one day.
+#[derive(PartialEq, Debug, Default, Clone, Copy)]
+pub struct DailyPractice {
+    pub seconds: u32,
+    pub sessions: u32,
+    pub characters: u32,
+    /// The goal of the day's last session that was recorded with one.
+    pub goal: Option<DailyGoal>,
+}
+
+impl Default for DailyGoal {
+    fn default() -> Self {
+        DailyGoal::Minutes(15)
+    }
+}
+
+impl DailyGoal {
+    /// Switches to the next kind of goal, starting from its default target.
+    pub fn next_kind(self) -> DailyGoal {
+        match self {
+            DailyGoal::Minutes(_) => DailyGoal::Sessions(5),
+            DailyGoal::Sessions(_) => DailyGoal::Characters(2000),
+            DailyGoal::Characters(_) => DailyGoal::Minutes(15),
+        }
+    }
+    /// Raises the target by one step, e.g. five more minutes.
+    pub fn increased(self) -> DailyGoal {
+        self.with_target(self.target() + self.step())
+    }
+    /// Lowers the target by one step, but never below a single step.
+    pub fn decreased(self) -> DailyGoal {
+        let step = self.step();
+        self.with_target(self.target().saturating_sub(step).max(step))
+    }
+    fn target(&self) -> u32 {
+        match self {
+            DailyGoal::Minutes(target)
+            | DailyGoal::Sessions(target)
+            | DailyGoal::Characters(target) => *target,
+        }
+    }
+    fn step(&self) -> u32 {
+        match self {
+            DailyGoal::Minutes(_) => 5,
+            DailyGoal::Sessions(_) => 1,
+            DailyGoal::Characters(_) => 500,
+        }
+    }
+    fn with_target(self, target: u32) -> DailyGoal {
+        match self {
+            DailyGoal::Minutes(_) => DailyGoal::Minutes(target),
+            DailyGoal::Sessions(_) => DailyGoal::Sessions(target),
+            DailyGoal::Characters(_) => DailyGoal::Characters(target),
+        }
+    }
+    /// Share of the goal reached, at most 1.0.
+    pub fn progress(&self, practice: &DailyPractice) -> f64 {
+        let (done, target) = match self {
+            DailyGoal::Minutes(minutes) => (practice.seconds as f64, 60.0 * *minutes as f64),
+            DailyGoal::Sessions(sessions) => (practice.sessions as f64, *sessions as f64),
+            DailyGoal::Characters(characters) => (practice.characters as f64, *characters as f64),
+        };
+        if target == 0.0 {
+            1.0
+        } else {
+            (done / target).min(1.0)
+        }
+    }
+
+    pub fn is_reached(&self, practice: &DailyPractice) -> bool {
+        self.progress(practice) >= 1.0
+    }
+
+    pub fn describe_progress(&self, practice: &DailyPractice) -> String {
+        match self {
+            DailyGoal::Minutes(minutes) => format!("{}/{} min", practice.seconds / 60, minutes),
+            DailyGoal::Sessions(sessions) => {
+                format!("{}/{} sessions", practice.sessions, sessions)
+            }
+            DailyGoal::Characters(characters) => {
+                format!("{}/{} characters", practice.characters, characters)
+            }
+        }
+    }
+}
+
+/// Sums up the records per day (in UTC).
+pub fn daily_practice<'a>(
+    records: impl Iterator<Item = &'a TrainingRecord>,
+) -> BTreeMap<NaiveDate, DailyPractice> {
+    let mut records: Vec<&TrainingRecord> = records.collect();
+    records.sort_by_key(|record| record.timestamp);
+    let mut practice: BTreeMap<NaiveDate, DailyPractice> = BTreeMap::new();
+    for record in records {
+        let day = practice
+            .entry(record.timestamp.naive_utc().date())
+            .or_default();
+        day.seconds += record.stats.duration_seconds;
+        day.sessions += 1;
+        day.characters += record.stats.typed_characters;
+        day.goal = record.daily_goal.or(day.goal);
+    }
+    practice
+}
+
+/// Progress for every day from `first_day` to `last_day` towards the goal
+/// of that day, `goal` for days recorded without one.
+pub fn goal_history(
+    practice: &BTreeMap<NaiveDate, DailyPractice>,
+    goal: DailyGoal,
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+) -> Vec<(NaiveDate, f64)> {
+    let mut history = Vec::new();
+    let mut day = first_day;
+    while day <= last_day {
+        let progress = match practice.get(&day) {
+            Some(day_practice) => day_practice.goal.unwrap_or(goal).progress(day_practice),
+            None => 0.0,
+        };
+        history.push((day, progress));
+        day += Duration::days(1);
+    }
+    history
+}
+
+#[cfg(test)]
+mod test_goals {
+    use chrono::{DateTime, Utc};
+    use pretty_assertions::assert_eq;
+
+    use crate::core::stats::record;
+
+    use super::*;
+
+    fn record_on(day: u32, words_per_minute: u16) -> TrainingRecord {
+        let mut record = record(words_per_minute, 0);
+        record.timestamp = DateTime::parse_from_rfc3339(&format!("2021-03-{:02}T12:00:00Z", day))
+            .unwrap()
+            .with_timezone(&Utc);
+        record
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2021, 3, day)
+    }
+
+    #[test]
+    fn practice_is_summed_up_per_day() {
+        let records = [record_on(1, 20), record_on(1, 30), record_on(2, 10)];
+        let practice = daily_practice(records.iter());
+        assert_eq!(
+            practice.get(&date(1)),
+            Some(&DailyPractice {
+                seconds: 120,
+                sessions: 2,
+                characters: 250,
+                goal: None
+            })
+        );
+        assert_eq!(practice.get(&date(2)).map(|day| day.sessions), Some(1))
+    }
+    #[test]
+    fn progress_is_capped_at_goal() {
+        let practice = DailyPractice {
+            seconds: 900,
+            sessions: 3,
+            characters: 1000,
+            goal: None,
+        };
+        assert_eq!(DailyGoal::Minutes(10).progress(&practice), 1.0);
+        assert_eq!(DailyGoal::Sessions(6).progress(&practice), 0.5);
+        assert!(!DailyGoal::Characters(2000).is_reached(&practice))
+    }
+    #[test]
+    fn target_does_not_drop_below_one_step() {
+        assert_eq!(DailyGoal::Minutes(5).decreased(), DailyGoal::Minutes(5));
+        assert_eq!(DailyGoal::Minutes(5).increased(), DailyGoal::Minutes(10));
+        assert_eq!(
+            DailyGoal::Sessions(3).next_kind(),
+            DailyGoal::Characters(2000)
+        )
+    }
+    #[test]
+    fn past_days_are_judged_by_their_own_goal() {
+        let mut records = [record_on(2, 20), record_on(1, 20), record_on(1, 20)];
+        records[0].daily_goal = Some(DailyGoal::Sessions(1));
+        records[1].daily_goal = Some(DailyGoal::Sessions(4));
+        let practice = daily_practice(records.iter());
+        let history = goal_history(&practice, DailyGoal::Sessions(2), date(1), date(2));
+        assert_eq!(history, vec![(date(1), 0.5), (date(2), 1.0)])
+    }
+    #[test]
+    fn history_contains_days_without_practice() {
+        let records = [record_on(1, 20), record_on(3, 20)];
+        let practice = daily_practice(records.iter());
+        let history = goal_history(&practice, DailyGoal::Sessions(2), date(1), date(3));
+        assert_eq!(
+            history,
+            vec![(date(1), 0.5), (date(2), 0.0), (date(3), 0.5)]
+        )
+    }
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+
+use crate::core::enums::Key;
+
+/// Modifiers held with a key, as far as the terminal reports them. Termion
+/// only reports Ctrl and Alt, shifted characters arrive as such.
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+/// Where an input event comes from.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum InputSource {
+    Terminal,
+    /// Played back from a recorded session.
+    Replay,
+    /// Read from the terminal and changed by an input translation, e.g. a
+    /// layout emulation.
+    Remap,
+}
+
+/// A key press with the time it was read from the terminal. Terminals only
+/// report presses, so there is no release time.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct InputEvent {
+    pub key: Key,
+    pub modifiers: Modifiers,
+    pub timestamp: DateTime<Utc>,
+    pub source: InputSource,
+}
+
+impl InputEvent {
+    /// A key read from the terminal at `timestamp` without further modifiers.
+    pub fn new(key: Key, timestamp: DateTime<Utc>) -> InputEvent {
+        let modifiers = Modifiers {
+            shift: false,
+            ctrl: matches!(key, Key::Ctrl(_)),
+            alt: matches!(key, Key::Alt(_)),
+        };
+        InputEvent {
+            key,
+            modifiers,
+            timestamp,
+            source: InputSource::Terminal,
+        }
+    }
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+    pub fn with_source(mut self, source: InputSource) -> Self {
+        self.source = source;
+        self
+    }
+    /// The event with the key replaced by an input translation. It keeps its
+    /// timestamp and only counts as remapped if the key changed.
+    pub fn remapped(self, key: Key) -> Self {
+        if key == self.key {
+            return self;
+        }
+        InputEvent {
+            key,
+            source: InputSource::Remap,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_input {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn only_changed_keys_are_remapped() {
+        let event = InputEvent::new(Key::Char('f'), Utc::now());
+        assert_eq!(event.remapped(Key::Char('f')).source, InputSource::Terminal);
+        let remapped = event.remapped(Key::Char('e'));
+        assert_eq!(remapped.source, InputSource::Remap);
+        assert_eq!(remapped.timestamp, event.timestamp)
+    }
+    #[test]
+    fn modifier_keys_set_their_modifier() {
+        let event = InputEvent::new(Key::Ctrl('k'), Utc::now());
+        assert!(event.modifiers.ctrl);
+        assert!(!event.modifiers.alt)
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::character::Character;
+
+/// The layer of a key, i.e. which modifiers have to be held to produce a
+/// character. The names follow the Neo family of layouts (Neo2, Bone, ...).
+#[derive(
+    PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize, Clone, Copy, Hash, Default,
+)]
+pub enum Layer {
+    #[default]
+    Base,
+    Shift,
+    Mod3,
+    Mod4,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 4] = [Layer::Base, Layer::Shift, Layer::Mod3, Layer::Mod4];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layer::Base => "Base",
+            Layer::Shift => "Shift",
+            Layer::Mod3 => "Mod3",
+            Layer::Mod4 => "Mod4",
+        }
+    }
+}
+
+/// Physical position of a key: rows count from the number row (0) down to
+/// the bottom letter row (3), columns from the leftmost key of a row.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize, Clone, Copy, Hash)]
+pub struct KeyPosition {
+    pub row: u8,
+    pub column: u8,
+}
+
+impl KeyPosition {
+    /// Finger used for this key when touch typing on a row-staggered
+    /// keyboard. The number row starts one key further left than the
+    /// letter rows.
+    pub fn finger(&self) -> Finger {
+        let column = if self.row == 0 {
+            self.column.saturating_sub(1)
+        } else {
+            self.column
+        };
+        match column {
+            0 => Finger::LeftPinky,
+            1 => Finger::LeftRing,
+            2 => Finger::LeftMiddle,
+            3 | 4 => Finger::LeftIndex,
+            5 | 6 => Finger::RightIndex,
+            7 => Finger::RightMiddle,
+            8 => Finger::RightRing,
+            _ => Finger::RightPinky,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize, Clone, Copy, Hash)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+impl Finger {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Finger::LeftPinky => "left pinky",
+            Finger::LeftRing => "left ring finger",
+            Finger::LeftMiddle => "left middle finger",
+            Finger::LeftIndex => "left index finger",
+            Finger::RightIndex => "right index finger",
+            Finger::RightMiddle => "right middle finger",
+            Finger::RightRing => "right ring finger",
+            Finger::RightPinky => "right pinky",
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct LayoutKey {
+    pub position: KeyPosition,
+    pub characters: BTreeMap<Layer, char>,
+}
+
+impl LayoutKey {
+    pub fn character(&self, layer: Layer) -> Option<char> {
+        self.characters.get(&layer).copied()
+    }
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct KeyboardLayout {
+    pub name: String,
+    keys: Vec<LayoutKey>,
+}
+
+impl KeyboardLayout {
+    /// Builds a layout from one string per row and layer, in the order of
+    /// `Layer::ALL`. Every char is a key, spaces mark keys without a character
+    /// on that layer.
+    pub fn from_rows(name: &str, rows: &[&[&str]]) -> KeyboardLayout {
+        let mut keys: Vec<LayoutKey> = Vec::new();
+        for (row, layers) in rows.iter().enumerate() {
+            for (layer, characters) in Layer::ALL.iter().zip(layers.iter()) {
+                for (column, character) in characters.chars().enumerate() {
+                    let position = KeyPosition {
+                        row: row as u8,
+                        column: column as u8,
+                    };
+                    let key = match keys.iter_mut().find(|key| key.position == position) {
+                        Some(key) => key,
+                        None => {
+                            keys.push(LayoutKey {
+                                position,
+                                characters: BTreeMap::new(),
+                            });
+                            keys.last_mut().unwrap()
+                        }
+                    };
+                    if character != ' ' {
+                        key.characters.insert(*layer, character);
+                    }
+                }
+            }
+        }
+        KeyboardLayout {
+            name: name.to_string(),
+            keys,
+        }
+    }
+
+    pub fn bone() -> KeyboardLayout {
+        KeyboardLayout::from_rows(
+            "Bone",
+            &[
+                &[" 1234567890-", " °§ℓ»«$€„“”—"],
+                &["jduaxphlmwß", "JDUAXPHLMWẞ", "…_[]^!<>=&ſ", "     ¡789+−"],
+                &["ctieobnrsgq", "CTIEOBNRSGQ", "\\/{}*?()-:@", "     ¿456,."],
+                &["fvüäöyz,.k", "FVÜÄÖYZ–•K", "#$|~`+%\"';", "     :123;"],
+            ],
+        )
+    }
+
+    pub fn qwertz() -> KeyboardLayout {
+        KeyboardLayout::from_rows(
+            "QWERTZ",
+            &[
+                &["^1234567890ß´", "°!\"§$%&/()=?`"],
+                &["qwertzuiopü+", "QWERTZUIOPÜ*"],
+                &["asdfghjklöä#", "ASDFGHJKLÖÄ'"],
+                &["yxcvbnm,.-", "YXCVBNM;:_"],
+            ],
+        )
+    }
+
+    pub fn qwerty() -> KeyboardLayout {
+        KeyboardLayout::from_rows(
+            "QWERTY",
+            &[
+                &["`1234567890-=", "~!@#$%^&*()_+"],
+                &["qwertyuiop[]", "QWERTYUIOP{}"],
+                &["asdfghjkl;'", "ASDFGHJKL:\""],
+                &["zxcvbnm,./", "ZXCVBNM<>?"],
+            ],
+        )
+    }
+
+    /// Looks up one of the built-in layouts by its case-insensitive name.
+    pub fn by_name(name: &str) -> Option<KeyboardLayout> {
+        match name.to_lowercase().as_str() {
+            "bone" => Some(KeyboardLayout::bone()),
+            "qwertz" => Some(KeyboardLayout::qwertz()),
+            "qwerty" => Some(KeyboardLayout::qwerty()),
+            _ => None,
+        }
+    }
+
+    pub fn keys(&self) -> &[LayoutKey] {
+        &self.keys
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.keys
+            .iter()
+            .map(|key| key.position.row as usize + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn row(&self, row: u8) -> Vec<&LayoutKey> {
+        let mut keys: Vec<&LayoutKey> = self
+            .keys
+            .iter()
+            .filter(|key| key.position.row == row)
+            .collect();
+        keys.sort_by_key(|key| key.position.column);
+        keys
+    }
+
+    /// Finds the key and layer producing `grapheme`, preferring lower layers
+    /// if a character is reachable on several of them.
+    pub fn find(&self, grapheme: &str) -> Option<(KeyPosition, Layer)> {
+        let mut chars = grapheme.chars();
+        let character = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
+        Layer::ALL.iter().find_map(|layer| {
+            self.keys
+                .iter()
+                .find(|key| key.character(*layer) == Some(character))
+                .map(|key| (key.position, *layer))
+        })
+    }
+
+    pub fn character_at(&self, position: KeyPosition, layer: Layer) -> Option<char> {
+        self.keys
+            .iter()
+            .find(|key| key.position == position)
+            .and_then(|key| key.character(layer))
+    }
+
+    pub fn layer_of(&self, grapheme: &str) -> Layer {
+        self.find(grapheme)
+            .map(|(_, layer)| layer)
+            .unwrap_or_default()
+    }
+
+    pub fn characters(&self, chars: &[char]) -> Vec<Character> {
+        chars
+            .iter()
+            .map(|c| Character::on_layer(*c, self.layer_of(&c.to_string())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_layout {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn finds_base_layer_character() {
+        assert_eq!(
+            KeyboardLayout::bone().find("e"),
+            Some((KeyPosition { row: 2, column: 3 }, Layer::Base))
+        )
+    }
+    #[test]
+    fn capital_is_on_shift_layer_of_same_key() {
+        assert_eq!(
+            KeyboardLayout::bone().find("E"),
+            Some((KeyPosition { row: 2, column: 3 }, Layer::Shift))
+        )
+    }
+    #[test]
+    fn symbols_are_found_on_upper_layers() {
+        let bone = KeyboardLayout::bone();
+        assert_eq!(bone.layer_of("{"), Layer::Mod3);
+        assert_eq!(bone.layer_of("5"), Layer::Base);
+        assert_eq!(bone.find("¿").map(|(_, layer)| layer), Some(Layer::Mod4));
+    }
+    #[test]
+    fn spaces_do_not_define_characters() {
+        assert_eq!(KeyboardLayout::bone().find(" "), None)
+    }
+    #[test]
+    fn characters_carry_their_layer() {
+        assert_eq!(
+            KeyboardLayout::bone().characters(&['e', 'E']),
+            vec![
+                Character::on_layer('e', Layer::Base),
+                Character::on_layer('E', Layer::Shift)
+            ]
+        )
+    }
+    #[test]
+    fn home_row_fingers() {
+        let bone = KeyboardLayout::bone();
+        let finger_of = |c: &str| bone.find(c).map(|(position, _)| position.finger());
+        assert_eq!(finger_of("c"), Some(Finger::LeftPinky));
+        assert_eq!(finger_of("e"), Some(Finger::LeftIndex));
+        assert_eq!(finger_of("n"), Some(Finger::RightIndex));
+        assert_eq!(finger_of("q"), Some(Finger::RightPinky));
+    }
+    #[test]
+    fn number_row_is_shifted_by_one_key() {
+        let bone = KeyboardLayout::bone();
+        let finger_of = |c: &str| bone.find(c).map(|(position, _)| position.finger());
+        assert_eq!(finger_of("1"), Some(Finger::LeftPinky));
+        assert_eq!(finger_of("3"), Some(Finger::LeftMiddle));
+        assert_eq!(finger_of("0"), Some(Finger::RightPinky));
+    }
+}
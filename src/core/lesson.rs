@@ -1,11 +1,22 @@
 use std::clone::Clone;
 
-use rand;
-use rand::seq::SliceRandom;
+use chrono::{Datelike, NaiveDate};
+use rand::seq::{index, SliceRandom};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::core::character::Character;
-use crate::core::weighting_strategy::WeightingStrategy;
+use crate::core::compose::ComposeSequence;
+use crate::core::constraints::{GenerationConstraints, WordRules};
+use crate::core::mastery::MasteryCriteria;
+use crate::core::sentences::Sentences;
+use crate::core::weighting_strategy::{FocusKey, ProvideKeyWeight, WeightingStrategy};
+use crate::core::word_length::WordLengthDistribution;
+
+/// Attempts to draw a word that follows the constraints.
+const MAX_WORD_DRAWS: usize = 100;
 
 #[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
 pub struct Lesson {
@@ -14,12 +25,100 @@ pub struct Lesson {
     weighting_strategy: WeightingStrategy,
     lesson_length: u32,
     word_length: u8,
+    #[serde(default)]
+    compose_sequences: Vec<ComposeSequence>,
+    #[serde(default)]
+    mastery_criteria: Option<MasteryCriteria>,
+    /// Replaces the fixed `word_length` if set.
+    #[serde(default)]
+    word_lengths: Option<WordLengthDistribution>,
+    #[serde(default)]
+    sentences: Option<Sentences>,
+    #[serde(default)]
+    constraints: Option<GenerationConstraints>,
+    /// Number of keys at the end of `keys` that this lesson introduces.
+    /// Missing in older save files, see `infer_new_keys`.
+    #[serde(default)]
+    introduced_keys: Option<usize>,
 }
 
 impl Lesson {
     pub fn name(&self) -> &str {
         self.name.as_str()
     }
+    pub fn lesson_length(&self) -> u32 {
+        self.lesson_length
+    }
+    pub fn word_length(&self) -> u8 {
+        self.word_length
+    }
+    pub fn word_lengths(&self) -> Option<&WordLengthDistribution> {
+        self.word_lengths.as_ref()
+    }
+    pub fn weighting_strategy(&self) -> &WeightingStrategy {
+        &self.weighting_strategy
+    }
+    pub fn compose_sequences(&self) -> &[ComposeSequence] {
+        &self.compose_sequences
+    }
+    /// Criteria overriding the ones of the course for this lesson.
+    pub fn mastery_criteria(&self) -> Option<&MasteryCriteria> {
+        self.mastery_criteria.as_ref()
+    }
+    pub fn with_mastery_criteria(mut self, mastery_criteria: MasteryCriteria) -> Lesson {
+        self.mastery_criteria = Some(mastery_criteria);
+        self
+    }
+    pub fn with_name(mut self, name: String) -> Lesson {
+        self.name = name;
+        self
+    }
+    pub fn with_lesson_length(mut self, lesson_length: u32) -> Lesson {
+        self.lesson_length = lesson_length;
+        self
+    }
+    pub fn with_word_length(mut self, word_length: u8) -> Lesson {
+        self.word_length = word_length;
+        self
+    }
+    /// Draws the length of each word instead of using the fixed one.
+    pub fn with_word_lengths(mut self, word_lengths: WordLengthDistribution) -> Lesson {
+        self.word_lengths = Some(word_lengths);
+        self
+    }
+    pub fn with_sentences(mut self, sentences: Sentences) -> Lesson {
+        self.sentences = Some(sentences);
+        self
+    }
+    pub fn with_constraints(mut self, constraints: GenerationConstraints) -> Lesson {
+        self.constraints = Some(constraints);
+        self
+    }
+    /// The keys this lesson adds to the one it was derived from.
+    pub fn new_keys(&self) -> &[Character] {
+        let introduced_keys = self.introduced_keys.unwrap_or(self.keys.len());
+        &self.keys[self.keys.len() - introduced_keys.min(self.keys.len())..]
+    }
+    /// Takes the new keys of a lesson from a save file that did not store
+    /// them from the lesson before it: the keys following that lesson's
+    /// keys, or all keys if the lesson does not start with them.
+    pub fn infer_new_keys(&mut self, previous: Option<&Lesson>) {
+        if self.introduced_keys.is_some() {
+            return;
+        }
+        let inherited_keys = match previous {
+            Some(previous) if self.keys.starts_with(&previous.keys) => previous.keys.len(),
+            _ => 0,
+        };
+        self.introduced_keys = Some(self.keys.len() - inherited_keys);
+    }
+    /// The longest word the lesson generates, without punctuation.
+    pub fn max_word_length(&self) -> u8 {
+        match &self.word_lengths {
+            Some(word_lengths) => word_lengths.max_length(),
+            None => self.word_length,
+        }
+    }
     pub fn add_key(
         &self,
         name: String,
@@ -34,6 +133,12 @@ impl Lesson {
             weighting_strategy,
             lesson_length: self.lesson_length,
             word_length: self.word_length,
+            compose_sequences: self.compose_sequences.clone(),
+            mastery_criteria: self.mastery_criteria.clone(),
+            word_lengths: self.word_lengths.clone(),
+            sentences: self.sentences.clone(),
+            constraints: self.constraints.clone(),
+            introduced_keys: Some(1),
         }
     }
     pub fn add_chars(
@@ -52,9 +157,53 @@ impl Lesson {
             weighting_strategy,
             lesson_length: self.lesson_length,
             word_length: self.word_length,
+            compose_sequences: self.compose_sequences.clone(),
+            mastery_criteria: self.mastery_criteria.clone(),
+            word_lengths: self.word_lengths.clone(),
+            sentences: self.sentences.clone(),
+            constraints: self.constraints.clone(),
+            introduced_keys: Some(chars.len()),
+        }
+    }
+
+    pub fn add_characters(
+        &self,
+        name: String,
+        characters: &[Character],
+        weighting_strategy: WeightingStrategy,
+    ) -> Lesson {
+        let mut new_keys = self.keys.clone();
+        new_keys.extend_from_slice(characters);
+        Lesson {
+            name,
+            keys: new_keys,
+            weighting_strategy,
+            lesson_length: self.lesson_length,
+            word_length: self.word_length,
+            compose_sequences: self.compose_sequences.clone(),
+            mastery_criteria: self.mastery_criteria.clone(),
+            word_lengths: self.word_lengths.clone(),
+            sentences: self.sentences.clone(),
+            constraints: self.constraints.clone(),
+            introduced_keys: Some(characters.len()),
         }
     }
 
+    /// Copy of this lesson that keeps its first `inherited_keys` keys and
+    /// introduces `characters` after them, e.g. to edit the keys a lesson
+    /// adds to the one it was derived from.
+    pub fn with_new_keys(
+        &self,
+        name: String,
+        inherited_keys: usize,
+        characters: &[Character],
+        weighting_strategy: WeightingStrategy,
+    ) -> Lesson {
+        let mut base = self.clone();
+        base.keys.truncate(inherited_keys);
+        base.add_characters(name, characters, weighting_strategy)
+    }
+
     pub fn from_chars(
         name: String,
         chars: &[char],
@@ -72,41 +221,425 @@ impl Lesson {
             weighting_strategy,
             lesson_length: char_count,
             word_length,
+            compose_sequences: Vec::new(),
+            mastery_criteria: None,
+            word_lengths: None,
+            sentences: None,
+            constraints: None,
+            introduced_keys: Some(chars.len()),
+        }
+    }
+    /// Copy of this lesson that draws the given keys `weight` times as often.
+    pub fn focus_on(&self, name: String, focused_keys: Vec<Character>, weight: f64) -> Lesson {
+        Lesson {
+            name,
+            weighting_strategy: WeightingStrategy::FocusKey(FocusKey {
+                focused_keys,
+                weight,
+            }),
+            ..self.clone()
+        }
+    }
+    pub fn keys(&self) -> &[Character] {
+        &self.keys
+    }
+    /// Derives a lesson that additionally trains the characters produced by
+    /// the given dead key or compose sequences.
+    pub fn add_compose_sequences(
+        &self,
+        name: String,
+        sequences: &[ComposeSequence],
+        weighting_strategy: WeightingStrategy,
+    ) -> Lesson {
+        let mut new_keys = self.keys.clone();
+        let mut compose_sequences = self.compose_sequences.clone();
+        for sequence in sequences {
+            new_keys.push(Character::from_grapheme(&sequence.result));
+            compose_sequences.push(sequence.clone());
+        }
+        Lesson {
+            name,
+            keys: new_keys,
+            weighting_strategy,
+            lesson_length: self.lesson_length,
+            word_length: self.word_length,
+            compose_sequences,
+            mastery_criteria: self.mastery_criteria.clone(),
+            word_lengths: self.word_lengths.clone(),
+            sentences: self.sentences.clone(),
+            constraints: self.constraints.clone(),
+            introduced_keys: Some(sequences.len()),
         }
     }
-    fn generate_word(&self) -> String {
-        let mut word = String::new();
-        let rng = &mut rand::thread_rng();
-        while word.len() < self.word_length as usize {
-            let next_key = self.keys.choose(rng).unwrap().value;
-            word.push(next_key);
+    /// Draws words until one follows the constraints, keeping the last one
+    /// if none does.
+    fn generate_word<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        rules: &WordRules,
+        previous_words: &[String],
+    ) -> String {
+        let mut word = self.draw_word(rng, rules);
+        for _ in 1..MAX_WORD_DRAWS {
+            if rules.accepts(&word, previous_words) {
+                break;
+            }
+            word = self.draw_word(rng, rules);
         }
         word
     }
+    fn draw_word<R: Rng + ?Sized>(&self, rng: &mut R, rules: &WordRules) -> String {
+        let word_length = match &self.word_lengths {
+            Some(word_lengths) => word_lengths.sample(rng),
+            None => self.word_length,
+        } as usize;
+        let required_new_keys = rules.required_new_keys(word_length);
+        let new_key_positions = if required_new_keys > 0 {
+            index::sample(rng, word_length, required_new_keys).into_vec()
+        } else {
+            Vec::new()
+        };
+        let mut word: Vec<&str> = Vec::new();
+        for position in 0..word_length {
+            let new_key = new_key_positions.contains(&position);
+            match self.draw_key(rng, rules, &word, new_key) {
+                Some(key) => word.push(key),
+                None => break,
+            }
+        }
+        word.concat()
+    }
+    /// Draws a key that may follow the keys of `word`. If no key with a
+    /// weight above 0 does, the rules are dropped, first the ones on the
+    /// previous keys, then the one for a new key, and finally the weights.
+    fn draw_key<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        rules: &WordRules,
+        word: &[&str],
+        new_key: bool,
+    ) -> Option<&str> {
+        let is_allowed_new_key = |key: &Character| !new_key || rules.is_new_key(&key.value);
+        let filters: [&dyn Fn(&Character) -> bool; 3] = [
+            &|key| is_allowed_new_key(key) && rules.allows_next(word, &key.value),
+            &is_allowed_new_key,
+            &|_| true,
+        ];
+        for filter in filters.iter() {
+            let key = self.keys.choose_weighted(rng, |key| {
+                if filter(key) {
+                    self.weighting_strategy.get_key_weight(key.clone())
+                } else {
+                    0.0
+                }
+            });
+            if let Ok(key) = key {
+                return Some(&key.value);
+            }
+        }
+        self.keys.choose(rng).map(|key| key.value.as_str())
+    }
     pub fn generate_lesson_content(&self) -> String {
-        let mut content = self.generate_word();
-        while content.len() < self.lesson_length as usize {
-            content = [content, self.generate_word()].join(" ");
+        self.generate_content(self.lesson_length)
+    }
+
+    /// Words of the lesson with at least `length` characters in total.
+    pub fn generate_content(&self, length: u32) -> String {
+        self.generate_content_with(length, &mut rand::thread_rng())
+    }
+
+    /// The same content for the same seed, e.g. to type a text again.
+    pub fn generate_lesson_content_with_seed(&self, seed: u64) -> String {
+        self.generate_content_with_seed(self.lesson_length, seed)
+    }
+
+    pub fn generate_content_with_seed(&self, length: u32, seed: u64) -> String {
+        self.generate_content_with(length, &mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Like `generate_content`, drawing the characters from `rng`. The
+    /// content is at most one word, its space and a punctuation mark longer
+    /// than `length`.
+    pub fn generate_content_with<R: Rng + ?Sized>(&self, length: u32, rng: &mut R) -> String {
+        let no_constraints = GenerationConstraints::default();
+        let rules = WordRules::new(
+            self.constraints.as_ref().unwrap_or(&no_constraints),
+            self.new_keys()
+                .iter()
+                .map(|key| key.value.as_str())
+                .collect(),
+        );
+        let mut generated_words: Vec<String> = Vec::new();
+        let mut words: Vec<String> = Vec::new();
+        let mut content_length = 0;
+        let mut sentence_words = 0;
+        let mut sentence_length = self
+            .sentences
+            .as_ref()
+            .map(|sentences| sentences.sample_word_count(rng));
+        while words.is_empty() || content_length < length as usize {
+            let mut word = self.generate_word(rng, &rules, &generated_words);
+            generated_words.push(word.clone());
+            if let (Some(sentences), Some(words_in_sentence)) = (&self.sentences, sentence_length) {
+                if sentence_words == 0 {
+                    word = sentences.capitalised(&word);
+                }
+                sentence_words += 1;
+                if sentence_words == words_in_sentence {
+                    word.extend(sentences.sample_end_mark(rng));
+                    sentence_words = 0;
+                    sentence_length = Some(sentences.sample_word_count(rng));
+                }
+            }
+            if !words.is_empty() {
+                content_length += 1;
+            }
+            content_length += word.graphemes(true).count();
+            words.push(word);
         }
-        content
+        // the content ends with a complete sentence
+        if let (Some(sentences), Some(last_word)) = (&self.sentences, words.last_mut()) {
+            if sentence_words > 0 {
+                last_word.extend(sentences.sample_end_mark(rng));
+            }
+        }
+        words.join(" ")
     }
 }
 
+/// Seed of the lesson of the day, the same for everyone on that day.
+pub fn lesson_of_the_day_seed(date: NaiveDate) -> u64 {
+    // spreads consecutive days over the seed range
+    (date.num_days_from_ce() as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
 #[cfg(test)]
 mod test_lesson {
     use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+    use proptest::test_runner::RngSeed;
+
+    use crate::core::constraints::{longest_run, same_finger_bigrams, SameFingerBigrams};
+    use crate::core::layout::KeyboardLayout;
 
     use super::*;
 
+    fn constrained_lesson(
+        keys: &str,
+        word_length: u8,
+        constraints: GenerationConstraints,
+    ) -> Lesson {
+        let keys: Vec<char> = keys.chars().collect();
+        Lesson::from_chars(
+            String::new(),
+            &keys,
+            80,
+            word_length,
+            WeightingStrategy::EqualWeight,
+        )
+        .with_constraints(constraints)
+    }
+
+    proptest! {
+        // the same cases on every run, constraints are only followed as far
+        // as the drawn words allow
+        #![proptest_config(ProptestConfig {
+            rng_seed: RngSeed::Fixed(0x5EED),
+            ..ProptestConfig::default()
+        })]
+
+        #[test]
+        fn repeated_runs_are_capped(
+            keys in "[a-h]{2,6}".prop_filter("two different keys", |keys| {
+                keys.chars().any(|key| !keys.starts_with(key))
+            }),
+            max_run in 1u8..4,
+            word_length in 1u8..9,
+            seed in any::<u64>(),
+        ) {
+            let lesson = constrained_lesson(&keys, word_length, GenerationConstraints {
+                max_repeated_run: Some(max_run),
+                ..GenerationConstraints::default()
+            });
+            for word in lesson.generate_lesson_content_with_seed(seed).split(' ') {
+                let graphemes: Vec<&str> = word.graphemes(true).collect();
+                prop_assert!(longest_run(&graphemes) <= max_run as usize, "{}", word);
+            }
+        }
+
+        #[test]
+        fn same_finger_bigrams_are_capped(
+            extra_keys in "[jduaxnrsg]{0,6}",
+            max_per_word in 0u8..3,
+            word_length in 2u8..9,
+            seed in any::<u64>(),
+        ) {
+            let lesson = constrained_lesson(
+                &format!("ctieo{}", extra_keys),
+                word_length,
+                GenerationConstraints {
+                    same_finger_bigrams: Some(SameFingerBigrams {
+                        layout: String::from("bone"),
+                        max_per_word,
+                    }),
+                    ..GenerationConstraints::default()
+                },
+            );
+            let bone = KeyboardLayout::bone();
+            for word in lesson.generate_lesson_content_with_seed(seed).split(' ') {
+                let graphemes: Vec<&str> = word.graphemes(true).collect();
+                prop_assert!(
+                    same_finger_bigrams(&bone, &graphemes) <= max_per_word as usize,
+                    "{}",
+                    word
+                );
+            }
+        }
+
+        #[test]
+        fn words_contain_the_minimum_share_of_new_keys(
+            new_keys in "[n-r]{1,3}",
+            share in 0.0..=1.0f64,
+            word_length in 1u8..9,
+            seed in any::<u64>(),
+        ) {
+            let new_keys: Vec<char> = new_keys.chars().collect();
+            let lesson = constrained_lesson("abcdefgh", word_length, GenerationConstraints {
+                min_new_key_share: Some(share),
+                ..GenerationConstraints::default()
+            })
+            .add_chars(String::new(), &new_keys, WeightingStrategy::EqualWeight);
+            for word in lesson.generate_lesson_content_with_seed(seed).split(' ') {
+                let new = word.chars().filter(|key| new_keys.contains(key)).count();
+                prop_assert!(new as f64 >= share * word.len() as f64 - 1e-9, "{}", word);
+            }
+        }
+
+        #[test]
+        fn words_are_not_repeated_within_the_distance(
+            keys in prop::sample::subsequence(vec!['a', 'b', 'c', 'd', 'e', 'f'], 3..=6),
+            distinct_words in 1usize..10,
+            seed in any::<u64>(),
+        ) {
+            let keys: String = keys.into_iter().collect();
+            let lesson = constrained_lesson(&keys, 3, GenerationConstraints {
+                distinct_words: Some(distinct_words),
+                ..GenerationConstraints::default()
+            });
+            let content = lesson.generate_lesson_content_with_seed(seed);
+            let words: Vec<&str> = content.split(' ').collect();
+            for (index, word) in words.iter().enumerate() {
+                let recent = &words[index.saturating_sub(distinct_words)..index];
+                prop_assert!(!recent.contains(word), "{}", content);
+            }
+        }
+    }
+
     #[test]
-    fn has_roughly_specified_length() {
-        let lesson = Lesson {
-            name: String::new(),
-            keys: vec![Character { value: 'a' }],
-            weighting_strategy: WeightingStrategy::EqualWeight,
-            lesson_length: 10,
-            word_length: 2,
+    fn keys_without_weight_are_drawn_if_nothing_else_is_left() {
+        let lesson = Lesson::from_chars(
+            String::new(),
+            &['a', 'b'],
+            20,
+            3,
+            WeightingStrategy::EqualWeight,
+        )
+        .add_chars(
+            String::new(),
+            &['c'],
+            WeightingStrategy::FocusKey(FocusKey {
+                focused_keys: vec![
+                    Character::new('a'),
+                    Character::new('b'),
+                    Character::new('c'),
+                ],
+                weight: 0.0,
+            }),
+        )
+        .with_constraints(GenerationConstraints {
+            max_repeated_run: Some(1),
+            min_new_key_share: Some(1.0),
+            ..GenerationConstraints::default()
+        });
+        let content = lesson.generate_lesson_content_with_seed(3);
+        assert!(content.split(' ').all(|word| word.len() == 3));
+        let empty = Lesson::from_chars(String::new(), &[], 5, 3, WeightingStrategy::EqualWeight);
+        assert_eq!(empty.generate_lesson_content_with_seed(3).trim(), "")
+    }
+
+    #[test]
+    fn new_keys_of_old_save_files_follow_the_previous_lesson() {
+        let lesson_1 = Lesson::from_chars(
+            String::new(),
+            &['a', 'b'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        );
+        let mut lesson_2 = Lesson {
+            introduced_keys: None,
+            ..lesson_1.add_chars(String::new(), &['c'], WeightingStrategy::EqualWeight)
+        };
+        let mut unrelated = Lesson {
+            introduced_keys: None,
+            ..Lesson::from_chars(String::new(), &['x'], 10, 2, WeightingStrategy::EqualWeight)
         };
+        lesson_2.infer_new_keys(Some(&lesson_1));
+        unrelated.infer_new_keys(Some(&lesson_2));
+        assert_eq!(lesson_2.new_keys(), &[Character::new('c')]);
+        assert_eq!(unrelated.new_keys(), &[Character::new('x')]);
+    }
+
+    #[test]
+    fn new_keys_are_replaced_after_the_inherited_ones() {
+        let lesson = Lesson::from_chars(
+            String::from("Lesson 1"),
+            &['a', 'b'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        )
+        .add_chars(
+            String::from("Lesson 2"),
+            &['c', 'd'],
+            WeightingStrategy::EqualWeight,
+        );
+        let edited = lesson.with_new_keys(
+            String::from("Edited"),
+            2,
+            &[Character::new('e')],
+            WeightingStrategy::EqualWeight,
+        );
+        assert_eq!(edited.name(), "Edited");
+        assert_eq!(
+            edited.keys(),
+            &[
+                Character::new('a'),
+                Character::new('b'),
+                Character::new('e')
+            ]
+        );
+        assert_eq!(edited.new_keys(), &[Character::new('e')]);
+    }
+
+    #[test]
+    fn new_keys_are_the_ones_added_last() {
+        let lesson = Lesson::from_chars(
+            String::new(),
+            &['a', 'b'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        );
+        assert_eq!(lesson.new_keys().len(), 2);
+        let lesson = lesson.add_chars(String::new(), &['c'], WeightingStrategy::EqualWeight);
+        assert_eq!(lesson.new_keys(), &[Character::new('c')]);
+    }
+
+    #[test]
+    fn has_roughly_specified_length() {
+        let lesson =
+            Lesson::from_chars(String::new(), &['a'], 10, 2, WeightingStrategy::EqualWeight);
         let lesson_length = lesson.generate_lesson_content().len() as u32;
         assert!(
             lesson.lesson_length <= lesson_length
@@ -116,35 +649,140 @@ mod test_lesson {
 
     #[test]
     fn content_does_not_start_or_end_with_whitespace() {
-        let lesson = Lesson {
-            name: String::new(),
-            keys: vec![Character { value: 'a' }],
-            weighting_strategy: WeightingStrategy::EqualWeight,
-            lesson_length: 10,
-            word_length: 2,
-        };
+        let lesson =
+            Lesson::from_chars(String::new(), &['a'], 10, 2, WeightingStrategy::EqualWeight);
         let lesson_content = lesson.generate_lesson_content();
         assert_eq!(lesson_content, lesson_content.trim());
     }
 
+    #[test]
+    fn length_of_multi_byte_content_is_counted_in_graphemes() {
+        let lesson = Lesson::from_chars(
+            String::new(),
+            &['ä', 'ß'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        );
+        let lesson_content = lesson.generate_lesson_content();
+        assert_eq!(lesson_content.graphemes(true).count(), 11);
+        assert!(lesson_content
+            .split(' ')
+            .all(|word| word.graphemes(true).count() == 2))
+    }
+
+    #[test]
+    fn same_seed_generates_the_same_content() {
+        let lesson = Lesson::from_chars(
+            String::new(),
+            &['a', 'b', 'c', 'd'],
+            40,
+            4,
+            WeightingStrategy::EqualWeight,
+        );
+        let content = lesson.generate_lesson_content_with_seed(7);
+        assert_eq!(content, lesson.generate_lesson_content_with_seed(7));
+        assert_ne!(content, lesson.generate_lesson_content_with_seed(8));
+        assert!(content
+            .split(' ')
+            .all(|word| word.len() == 4 && word.chars().all(|c| "abcd".contains(c))))
+    }
+
+    #[test]
+    fn lesson_of_the_day_changes_daily() {
+        let today = NaiveDate::from_ymd(2021, 6, 1);
+        assert_eq!(
+            lesson_of_the_day_seed(today),
+            lesson_of_the_day_seed(NaiveDate::from_ymd(2021, 6, 1))
+        );
+        assert_ne!(
+            lesson_of_the_day_seed(today),
+            lesson_of_the_day_seed(today.succ())
+        )
+    }
+
+    #[test]
+    fn length_stays_within_bounds_with_drawn_word_lengths() {
+        let lesson = Lesson::from_chars(
+            String::new(),
+            &['a', 'b'],
+            30,
+            4,
+            WeightingStrategy::EqualWeight,
+        )
+        .with_word_lengths(WordLengthDistribution::Uniform { min: 1, max: 7 });
+        for seed in 0..50 {
+            let content = lesson.generate_lesson_content_with_seed(seed);
+            let length = content.graphemes(true).count() as u32;
+            assert!((30..=30 + 7).contains(&length), "{:?}", content);
+            assert!(content.split(' ').all(|word| (1..=7).contains(&word.len())));
+        }
+    }
+
+    #[test]
+    fn sentences_are_capitalised_and_punctuated() {
+        let lesson = Lesson::from_chars(
+            String::new(),
+            &['a', 'b'],
+            60,
+            3,
+            WeightingStrategy::EqualWeight,
+        )
+        .with_sentences(Sentences {
+            min_words: 2,
+            max_words: 2,
+            capitalise: true,
+            end_marks: vec!['.'],
+        });
+        for seed in 0..20 {
+            let content = lesson.generate_lesson_content_with_seed(seed);
+            let length = content.graphemes(true).count() as u32;
+            assert!((60..=60 + 3 + 1).contains(&length), "{:?}", content);
+            assert!(content.ends_with('.'));
+            for sentence in content.split_terminator(". ") {
+                let words: Vec<_> = sentence.trim_end_matches('.').split(' ').collect();
+                assert!(words.len() <= 2, "{:?}", content);
+                assert!(words[0].starts_with(|c: char| c.is_uppercase()));
+                assert!(words[1..]
+                    .iter()
+                    .all(|word| word.chars().all(|c| c.is_lowercase())));
+            }
+        }
+    }
+
+    #[test]
+    fn combining_characters_count_as_one_grapheme() {
+        let lesson = Lesson::from_chars(String::new(), &[], 4, 4, WeightingStrategy::EqualWeight)
+            .add_characters(
+                String::new(),
+                &[Character::from_grapheme("e\u{301}")],
+                WeightingStrategy::EqualWeight,
+            );
+        let lesson_content = lesson.generate_lesson_content();
+        assert_eq!(lesson_content, "e\u{301}".repeat(4));
+    }
+
     #[test]
     fn append_key_to_lesson() {
-        let original_key = Character { value: 'a' };
-        let original_lesson = Lesson {
-            name: String::from("original_lesson"),
-            keys: vec![original_key.clone()],
-            weighting_strategy: WeightingStrategy::EqualWeight,
-            lesson_length: 10,
-            word_length: 2,
-        };
-        let extra_key = Character { value: 'b' };
+        let original_key = Character::new('a');
+        let original_lesson = Lesson::from_chars(
+            String::from("original_lesson"),
+            &['a'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        );
+        let extra_key = Character::new('b');
 
         let expected_lesson = Lesson {
-            name: String::from("lesson name"),
             keys: vec![original_key.clone(), extra_key.clone()],
-            weighting_strategy: WeightingStrategy::EqualWeight,
-            lesson_length: 10,
-            word_length: 2,
+            ..Lesson::from_chars(
+                String::from("lesson name"),
+                &['a'],
+                10,
+                2,
+                WeightingStrategy::EqualWeight,
+            )
         };
         let extended_lesson = original_lesson.add_key(
             String::from("lesson name"),
@@ -154,4 +792,67 @@ mod test_lesson {
 
         assert_eq!(extended_lesson, expected_lesson)
     }
+
+    #[test]
+    fn add_compose_sequences_adds_resulting_characters() {
+        let original_lesson = Lesson::from_chars(
+            String::from("original_lesson"),
+            &['e'],
+            10,
+            2,
+            WeightingStrategy::EqualWeight,
+        );
+        let acute_e = ComposeSequence::dead_key('´', 'e', 'é');
+
+        let extended_lesson = original_lesson.add_compose_sequences(
+            String::from("lesson name"),
+            &[ComposeSequence::dead_key('´', 'e', 'é')],
+            WeightingStrategy::EqualWeight,
+        );
+
+        assert_eq!(
+            extended_lesson.keys,
+            vec![Character::new('e'), Character::new('é')]
+        );
+        assert_eq!(extended_lesson.compose_sequences(), &[acute_e])
+    }
+
+    #[test]
+    fn focused_keys_are_drawn_more_often() {
+        let lesson = Lesson::from_chars(
+            String::new(),
+            &['a', 'b'],
+            1000,
+            5,
+            WeightingStrategy::EqualWeight,
+        )
+        .focus_on(String::from("focus"), vec![Character::new('b')], 9.0);
+        let content = lesson.generate_lesson_content();
+        let a_count = content.matches('a').count();
+        let b_count = content.matches('b').count();
+        assert!(b_count > 4 * a_count)
+    }
+
+    #[test]
+    fn derived_lessons_draw_keys_by_their_weighting() {
+        let lesson = Lesson::from_chars(
+            String::new(),
+            &['a'],
+            1000,
+            5,
+            WeightingStrategy::EqualWeight,
+        )
+        .add_chars(
+            String::new(),
+            &['b'],
+            WeightingStrategy::FocusKey(FocusKey {
+                focused_keys: vec![Character::new('b')],
+                weight: 9.0,
+            }),
+        );
+        let content = lesson.generate_lesson_content_with_seed(7);
+        let a_count = content.matches('a').count();
+        let b_count = content.matches('b').count();
+        assert!(b_count > 4 * a_count)
+    }
 }
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::stats::TrainingRecord;
+
+/// A lesson counts as mastered once the average of its last `session_count`
+/// sessions reaches both the net typing speed and the accuracy.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct MasteryCriteria {
+    pub min_net_words_per_minute: u16,
+    pub min_accuracy: f64,
+    pub session_count: usize,
+}
+
+impl MasteryCriteria {
+    pub fn is_met_by(&self, records: &[TrainingRecord]) -> bool {
+        if self.session_count == 0 {
+            return true;
+        }
+        if records.len() < self.session_count {
+            return false;
+        }
+        let last_records = &records[records.len() - self.session_count..];
+        let average_net_words_per_minute = last_records
+            .iter()
+            .map(|record| record.stats.net_words_per_minute() as f64)
+            .sum::<f64>()
+            / self.session_count as f64;
+        let average_accuracy = last_records
+            .iter()
+            .map(|record| record.stats.accuracy())
+            .sum::<f64>()
+            / self.session_count as f64;
+        average_net_words_per_minute >= self.min_net_words_per_minute as f64
+            && average_accuracy >= self.min_accuracy
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum LessonState {
+    Locked,
+    Unlocked,
+    Mastered,
+}
+
+#[cfg(test)]
+mod test_mastery {
+    use crate::core::stats::record;
+
+    use super::*;
+
+    fn criteria() -> MasteryCriteria {
+        MasteryCriteria {
+            min_net_words_per_minute: 20,
+            min_accuracy: 0.9,
+            session_count: 2,
+        }
+    }
+
+    #[test]
+    fn not_met_without_enough_sessions() {
+        assert!(!criteria().is_met_by(&[record(50, 0)]))
+    }
+    #[test]
+    fn met_by_average_of_last_sessions() {
+        let records = [record(5, 0), record(18, 0), record(24, 0)];
+        assert!(criteria().is_met_by(&records))
+    }
+    #[test]
+    fn not_met_if_too_slow() {
+        let records = [record(18, 0), record(20, 0)];
+        assert!(!criteria().is_met_by(&records))
+    }
+    #[test]
+    fn not_met_if_too_inaccurate() {
+        let records = [record(100, 60), record(100, 60)];
+        assert!(!criteria().is_met_by(&records))
+    }
+}
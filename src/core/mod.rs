@@ -1,7 +1,19 @@
+pub mod achievements;
 pub mod character;
+pub mod compose;
+pub mod constraints;
 pub mod enums;
+pub mod ghost;
+pub mod goals;
+pub mod input;
+pub mod layout;
 pub mod lesson;
+pub mod mastery;
+pub mod routine;
+pub mod scheduling;
+pub mod sentences;
 pub mod stats;
 pub mod typing_errors;
 pub mod typing_speed;
 pub mod weighting_strategy;
+pub mod word_length;
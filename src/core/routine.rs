@@ -0,0 +1,79 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+/// Number of characters generated for steps that are limited by time. It
+/// has to last even for very fast typists.
+const CHARACTERS_PER_SECOND_LIMIT: u32 = 15;
+
+/// What is typed in a step of a routine.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub enum StepSource {
+    Lesson {
+        lesson_index: usize,
+    },
+    /// A drill of the recently most missed keys, skipped if there are none.
+    WeakKeys,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum StepLimit {
+    Characters(u32),
+    Seconds(u32),
+}
+
+impl StepLimit {
+    /// Length of the content to generate for the step.
+    pub fn content_length(&self) -> u32 {
+        match self {
+            StepLimit::Characters(characters) => *characters,
+            StepLimit::Seconds(seconds) => seconds * CHARACTERS_PER_SECOND_LIMIT,
+        }
+    }
+    pub fn time_limit(&self) -> Option<Duration> {
+        match self {
+            StepLimit::Characters(_) => None,
+            StepLimit::Seconds(seconds) => Some(Duration::seconds(*seconds as i64)),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct RoutineStep {
+    pub source: StepSource,
+    pub limit: StepLimit,
+}
+
+/// Lessons that are practiced back to back, e.g. as a warm-up.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct Routine {
+    pub name: String,
+    pub steps: Vec<RoutineStep>,
+}
+
+impl Routine {
+    pub fn new(name: &str) -> Routine {
+        Routine {
+            name: name.to_string(),
+            steps: Vec::new(),
+        }
+    }
+    pub fn then(mut self, source: StepSource, limit: StepLimit) -> Self {
+        self.steps.push(RoutineStep { source, limit });
+        self
+    }
+}
+
+#[cfg(test)]
+mod test_routine {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn time_limited_steps_get_enough_content() {
+        let limit = StepLimit::Seconds(120);
+        assert_eq!(limit.time_limit(), Some(Duration::minutes(2)));
+        assert_eq!(limit.content_length(), 1800);
+        assert_eq!(StepLimit::Characters(300).time_limit(), None)
+    }
+}
@@ -0,0 +1,178 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::core::mastery::MasteryCriteria;
+use crate::core::stats::TrainingRecord;
+
+const INITIAL_EASE_FACTOR: f64 = 2.5;
+const MINIMUM_EASE_FACTOR: f64 = 1.3;
+
+/// Review schedule of a lesson following the SM-2 algorithm.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewState {
+    pub repetitions: u32,
+    pub interval_days: u32,
+    pub ease_factor: f64,
+    pub due: DateTime<Utc>,
+}
+
+impl ReviewState {
+    pub fn new(now: DateTime<Utc>) -> ReviewState {
+        ReviewState {
+            repetitions: 0,
+            interval_days: 0,
+            ease_factor: INITIAL_EASE_FACTOR,
+            due: now,
+        }
+    }
+
+    /// Schedules the next review after a review of the given quality
+    /// (0 = failed completely, 5 = perfect).
+    pub fn review(&self, quality: u8, reviewed_at: DateTime<Utc>) -> ReviewState {
+        let quality = quality.min(5);
+        let (repetitions, interval_days) = if quality >= 3 {
+            let interval_days = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval_days as f64 * self.ease_factor).round() as u32,
+            };
+            (self.repetitions + 1, interval_days)
+        } else {
+            (0, 1)
+        };
+        let missing_quality = (5 - quality) as f64;
+        let ease_factor = (self.ease_factor
+            + (0.1 - missing_quality * (0.08 + missing_quality * 0.02)))
+            .max(MINIMUM_EASE_FACTOR);
+        ReviewState {
+            repetitions,
+            interval_days,
+            ease_factor,
+            due: reviewed_at + Duration::days(interval_days as i64),
+        }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.due <= now
+    }
+
+    /// Replays the given records in order. Sessions before a lesson was due
+    /// again do not count as review.
+    pub fn from_records(
+        records: &[TrainingRecord],
+        criteria: Option<&MasteryCriteria>,
+    ) -> Option<ReviewState> {
+        let first_record = records.first()?;
+        let mut state = ReviewState::new(first_record.timestamp);
+        for record in records {
+            if state.is_due(record.timestamp) {
+                state = state.review(review_quality(record, criteria), record.timestamp);
+            }
+        }
+        Some(state)
+    }
+}
+
+/// Rates a session on the SM-2 quality scale, mostly by accuracy. Without
+/// reaching the speed of the mastery criteria a session is rated at most 3.
+pub fn review_quality(record: &TrainingRecord, criteria: Option<&MasteryCriteria>) -> u8 {
+    let accuracy = record.stats.accuracy();
+    let fast_enough = match criteria {
+        Some(criteria) => record.stats.net_words_per_minute() >= criteria.min_net_words_per_minute,
+        None => true,
+    };
+    let quality = if accuracy >= 0.98 {
+        5
+    } else if accuracy >= 0.95 {
+        4
+    } else if accuracy >= 0.9 {
+        3
+    } else if accuracy >= 0.8 {
+        2
+    } else if accuracy >= 0.6 {
+        1
+    } else {
+        0
+    };
+    if fast_enough {
+        quality
+    } else {
+        quality.min(3)
+    }
+}
+
+#[cfg(test)]
+mod test_scheduling {
+    use pretty_assertions::assert_eq;
+
+    use crate::core::stats::record;
+    use crate::wrapper::fake_clock::FakeClock;
+
+    use super::*;
+
+    fn record_at(timestamp: DateTime<Utc>, words_per_minute: u16, errors: u16) -> TrainingRecord {
+        let mut record = record(words_per_minute, errors);
+        record.timestamp = timestamp;
+        record
+    }
+
+    #[test]
+    fn successful_reviews_grow_the_interval() {
+        let now = FakeClock::now();
+        let first = ReviewState::new(now).review(5, now);
+        assert_eq!(first.interval_days, 1);
+        let second = first.review(5, first.due);
+        assert_eq!(second.interval_days, 6);
+        let third = second.review(5, second.due);
+        assert_eq!(third.interval_days, 16);
+        assert_eq!(third.due, second.due + Duration::days(16))
+    }
+    #[test]
+    fn failed_review_starts_over() {
+        let now = FakeClock::now();
+        let state = ReviewState::new(now).review(5, now).review(5, now);
+        let failed = state.review(1, now);
+        assert_eq!(failed.repetitions, 0);
+        assert_eq!(failed.interval_days, 1);
+        assert!(failed.ease_factor < state.ease_factor)
+    }
+    #[test]
+    fn ease_factor_does_not_drop_below_minimum() {
+        let now = FakeClock::now();
+        let mut state = ReviewState::new(now);
+        for _ in 0..10 {
+            state = state.review(0, now);
+        }
+        assert_eq!(state.ease_factor, MINIMUM_EASE_FACTOR)
+    }
+    #[test]
+    fn sessions_before_due_date_are_not_reviews() {
+        let start = FakeClock::now();
+        let records = [
+            record_at(start, 30, 0),
+            record_at(start + Duration::hours(2), 30, 0),
+            record_at(start + Duration::days(1), 30, 0),
+        ];
+        let state = ReviewState::from_records(&records, None).unwrap();
+        assert_eq!(state.repetitions, 2);
+        assert_eq!(state.due, start + Duration::days(7))
+    }
+    #[test]
+    fn lesson_becomes_due_when_clock_advances() {
+        let records = [record_at(FakeClock::now(), 30, 0)];
+        let state = ReviewState::from_records(&records, None).unwrap();
+        assert!(!state.is_due(FakeClock::now()));
+        FakeClock::advance(Duration::days(1));
+        assert!(state.is_due(FakeClock::now()))
+    }
+    #[test]
+    fn slow_sessions_are_rated_at_most_3() {
+        let criteria = MasteryCriteria {
+            min_net_words_per_minute: 40,
+            min_accuracy: 0.9,
+            session_count: 1,
+        };
+        assert_eq!(review_quality(&record(30, 0), Some(&criteria)), 3);
+        assert_eq!(review_quality(&record(30, 0), None), 5)
+    }
+}
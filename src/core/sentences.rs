@@ -0,0 +1,74 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Groups the generated words of a lesson into sentences, which start with a
+/// capital letter and end with a punctuation mark.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct Sentences {
+    pub min_words: u8,
+    pub max_words: u8,
+    pub capitalise: bool,
+    /// Marks drawn equally often to end a sentence, repeating one makes it
+    /// more common. Without marks sentences are not punctuated.
+    pub end_marks: Vec<char>,
+}
+
+impl Default for Sentences {
+    fn default() -> Self {
+        Sentences {
+            min_words: 4,
+            max_words: 10,
+            capitalise: true,
+            end_marks: vec!['.', '.', '.', '?', '!'],
+        }
+    }
+}
+
+impl Sentences {
+    pub fn sample_word_count<R: Rng + ?Sized>(&self, rng: &mut R) -> u8 {
+        let shortest = self.min_words.min(self.max_words).max(1);
+        let longest = self.min_words.max(self.max_words).max(1);
+        rng.gen_range(shortest..=longest)
+    }
+
+    pub fn sample_end_mark<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<char> {
+        self.end_marks.choose(rng).copied()
+    }
+
+    /// The word with its first grapheme in upper case, if capitalising is on
+    /// and the upper case is a single grapheme too, which "ß" is not.
+    pub fn capitalised(&self, word: &str) -> String {
+        let mut graphemes = word.graphemes(true);
+        let first = match graphemes.next() {
+            Some(first) if self.capitalise => first,
+            _ => return word.to_string(),
+        };
+        let upper_case = first.to_uppercase();
+        if upper_case.graphemes(true).count() != 1 {
+            return word.to_string();
+        }
+        upper_case + graphemes.as_str()
+    }
+}
+
+#[cfg(test)]
+mod test_sentences {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn first_grapheme_is_capitalised() {
+        let sentences = Sentences::default();
+        assert_eq!(sentences.capitalised("äb"), "Äb");
+        assert_eq!(sentences.capitalised("ßa"), "ßa");
+        assert_eq!(sentences.capitalised("e\u{301}a"), "E\u{301}a");
+        let lower_case = Sentences {
+            capitalise: false,
+            ..Sentences::default()
+        };
+        assert_eq!(lower_case.capitalised("ab"), "ab")
+    }
+}
@@ -1,6 +1,10 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::core::goals::DailyGoal;
+use crate::core::layout::Layer;
 use crate::core::typing_errors::TypingErrors;
 use crate::core::typing_speed::TypingSpeed;
 
@@ -8,9 +12,165 @@ use crate::core::typing_speed::TypingSpeed;
 pub struct TrainingStatistics {
     pub errors: TypingErrors,
     pub typing_speed: TypingSpeed,
+    #[serde(default)]
+    pub layers: BTreeMap<Layer, LayerStatistics>,
+    #[serde(default)]
+    pub typed_characters: u32,
+    #[serde(default)]
+    pub duration_seconds: u32,
+}
+
+/// Nothing typed yet.
+impl Default for TrainingStatistics {
+    fn default() -> Self {
+        TrainingStatistics {
+            errors: TypingErrors::default(),
+            typing_speed: TypingSpeed::CharactersPerMinute(0),
+            layers: BTreeMap::new(),
+            typed_characters: 0,
+            duration_seconds: 0,
+        }
+    }
+}
+
+impl TrainingStatistics {
+    /// Share of keystrokes that were correct, 1.0 if nothing was typed.
+    pub fn accuracy(&self) -> f64 {
+        let keystrokes = self.typed_characters + self.errors.total_error_count as u32;
+        if keystrokes == 0 {
+            1.0
+        } else {
+            self.typed_characters as f64 / keystrokes as f64
+        }
+    }
+
+    /// Words per minute reduced by the errors made per minute.
+    pub fn net_words_per_minute(&self) -> u16 {
+        let gross_words_per_minute = self.typing_speed.words_per_minute();
+        if self.duration_seconds == 0 {
+            return gross_words_per_minute;
+        }
+        let errors_per_minute = 60 * self.errors.total_error_count as u32 / self.duration_seconds;
+        gross_words_per_minute.saturating_sub(errors_per_minute as u16)
+    }
 }
+
+/// Typed characters and errors for the characters of one layer. Errors are
+/// split into hitting the wrong key and hitting the right key with the wrong
+/// modifier.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct LayerStatistics {
+    pub typed_characters: u16,
+    pub base_key_errors: u16,
+    pub modifier_errors: u16,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct TrainingRecord {
     pub timestamp: DateTime<Utc>,
     pub stats: TrainingStatistics,
+    /// The keystrokes of the session, missing for older records.
+    #[serde(default)]
+    pub recording: Option<Recording>,
+    /// The seed the content was generated from, to type it again.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// The daily goal when the session was recorded, so that past days are
+    /// judged by the goal of their time. Missing in older save files.
+    #[serde(default)]
+    pub daily_goal: Option<DailyGoal>,
+}
+
+impl TrainingRecord {
+    /// A record with the statistics of a session and nothing else.
+    pub fn new(timestamp: DateTime<Utc>, stats: TrainingStatistics) -> TrainingRecord {
+        TrainingRecord {
+            timestamp,
+            stats,
+            recording: None,
+            seed: None,
+            daily_goal: None,
+        }
+    }
+    pub fn with_recording(mut self, recording: Recording) -> Self {
+        self.recording = Some(recording);
+        self
+    }
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// A one minute session with the given speed and error count.
+#[cfg(test)]
+pub fn record(words_per_minute: u16, errors: u16) -> TrainingRecord {
+    TrainingRecord::new(
+        Utc::now(),
+        TrainingStatistics {
+            errors: TypingErrors {
+                total_error_count: errors,
+                ..Default::default()
+            },
+            typing_speed: TypingSpeed::WordsPerMinute(words_per_minute),
+            typed_characters: 5 * words_per_minute as u32,
+            duration_seconds: 60,
+            ..Default::default()
+        },
+    )
+}
+
+/// Everything needed to play a session back: the content and each typed
+/// character with its time since the first keystroke. Paused time is left
+/// out of the offsets.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Recording {
+    pub content: String,
+    pub keystrokes: Vec<Keystroke>,
+    #[serde(default)]
+    pub time_limit_seconds: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Keystroke {
+    pub offset_milliseconds: u32,
+    pub key: char,
+}
+#[cfg(test)]
+mod test_stats {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn statistics(typed_characters: u32, errors: u16, duration_seconds: u32) -> TrainingStatistics {
+        TrainingStatistics {
+            errors: TypingErrors {
+                total_error_count: errors,
+                ..Default::default()
+            },
+            typing_speed: TypingSpeed::CharactersPerMinute(
+                (60 * typed_characters / duration_seconds.max(1)) as u16,
+            ),
+            layers: BTreeMap::new(),
+            typed_characters,
+            duration_seconds,
+        }
+    }
+
+    #[test]
+    fn accuracy_is_share_of_correct_keystrokes() {
+        assert_eq!(statistics(90, 10, 60).accuracy(), 0.9)
+    }
+    #[test]
+    fn accuracy_without_keystrokes_is_perfect() {
+        assert_eq!(statistics(0, 0, 0).accuracy(), 1.0)
+    }
+    #[test]
+    fn net_words_per_minute_subtracts_errors_per_minute() {
+        assert_eq!(statistics(200, 10, 60).net_words_per_minute(), 30)
+    }
+    #[test]
+    fn net_words_per_minute_does_not_go_below_zero() {
+        assert_eq!(statistics(10, 50, 60).net_words_per_minute(), 0)
+    }
 }
@@ -1,6 +1,15 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
 pub struct TypingErrors {
     pub total_error_count: u16,
+    /// Errors on characters typed through a compose sequence, counted per
+    /// resulting character and indexed by the sequence step that went wrong.
+    #[serde(default)]
+    pub sequence_step_errors: BTreeMap<String, Vec<u16>>,
+    /// Errors counted per expected character.
+    #[serde(default)]
+    pub errors_by_key: BTreeMap<String, u16>,
 }
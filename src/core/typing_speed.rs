@@ -13,6 +13,12 @@ impl TypingSpeed {
             TypingSpeed::CharactersPerMinute(cpm) => cpm / 5,
         }
     }
+    pub fn characters_per_minute(self) -> u16 {
+        match self {
+            TypingSpeed::WordsPerMinute(wpm) => wpm.saturating_mul(5),
+            TypingSpeed::CharactersPerMinute(cpm) => cpm,
+        }
+    }
 }
 #[cfg(test)]
 mod test_training_session {
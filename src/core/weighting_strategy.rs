@@ -48,27 +48,27 @@ mod test_weighting_strategy {
     #[test]
     fn always_eq_weight_always_returns_1() {
         let strategy = WeightingStrategy::EqualWeight;
-        let current_key = Character { value: 'a' };
+        let current_key = Character::new('a');
         assert_eq!(strategy.get_key_weight(current_key), 1.0)
     }
 
     #[test]
     fn focus_key_always_returns_1_for_non_focused_key() {
         let strategy = WeightingStrategy::FocusKey(FocusKey {
-            focused_keys: vec![Character { value: 'a' }],
+            focused_keys: vec![Character::new('a')],
             weight: 10.0,
         });
-        let current_key = Character { value: 'b' };
+        let current_key = Character::new('b');
         assert_eq!(strategy.get_key_weight(current_key), 1.0)
     }
 
     #[test]
     fn focus_key_always_returns_weight_for_focused_key() {
         let strategy = WeightingStrategy::FocusKey(FocusKey {
-            focused_keys: vec![Character { value: 'a' }],
+            focused_keys: vec![Character::new('a')],
             weight: 10.0,
         });
-        let current_key = Character { value: 'a' };
+        let current_key = Character::new('a');
         assert_eq!(strategy.get_key_weight(current_key), 10.0)
     }
 }
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How long the generated words of a lesson are, in graphemes.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub enum WordLengthDistribution {
+    /// Every length from `min` to `max` is equally likely.
+    Uniform { min: u8, max: u8 },
+    /// Lengths drawn as often as they occur relative to each other, e.g. in
+    /// a corpus of the language.
+    Frequencies(BTreeMap<u8, u32>),
+}
+
+impl WordLengthDistribution {
+    /// Counts the lengths of the words in `corpus`. Punctuation around words
+    /// is not counted. `None` if the corpus has no words.
+    pub fn fitted_to(corpus: &str) -> Option<WordLengthDistribution> {
+        let mut frequencies: BTreeMap<u8, u32> = BTreeMap::new();
+        for word in corpus.unicode_words() {
+            let length = word.graphemes(true).count().min(u8::MAX as usize) as u8;
+            *frequencies.entry(length).or_default() += 1;
+        }
+        if frequencies.is_empty() {
+            None
+        } else {
+            Some(WordLengthDistribution::Frequencies(frequencies))
+        }
+    }
+
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u8 {
+        match self {
+            WordLengthDistribution::Uniform { min, max } => {
+                let (shortest, longest) = self.bounds(*min, *max);
+                rng.gen_range(shortest..=longest)
+            }
+            WordLengthDistribution::Frequencies(frequencies) => {
+                let lengths: Vec<(&u8, &u32)> = frequencies
+                    .iter()
+                    .filter(|(length, _)| **length > 0)
+                    .collect();
+                lengths
+                    .choose_weighted(rng, |(_, frequency)| **frequency)
+                    .map(|(length, _)| **length)
+                    .unwrap_or(1)
+            }
+        }
+    }
+
+    /// The longest word that can be drawn.
+    pub fn max_length(&self) -> u8 {
+        match self {
+            WordLengthDistribution::Uniform { min, max } => self.bounds(*min, *max).1,
+            WordLengthDistribution::Frequencies(frequencies) => frequencies
+                .iter()
+                .filter(|(_, frequency)| **frequency > 0)
+                .map(|(length, _)| *length)
+                .max()
+                .unwrap_or(1),
+        }
+    }
+
+    /// Words have at least one grapheme, and swapped bounds are accepted.
+    fn bounds(&self, min: u8, max: u8) -> (u8, u8) {
+        (min.min(max).max(1), min.max(max).max(1))
+    }
+}
+
+#[cfg(test)]
+mod test_word_length {
+    use pretty_assertions::assert_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    #[test]
+    fn uniform_lengths_stay_in_range() {
+        let distribution = WordLengthDistribution::Uniform { min: 2, max: 5 };
+        let rng = &mut ChaCha8Rng::seed_from_u64(1);
+        let lengths: Vec<u8> = (0..200).map(|_| distribution.sample(rng)).collect();
+        assert!(lengths.iter().all(|length| (2..=5).contains(length)));
+        assert!((2..=5).all(|length| lengths.contains(&length)));
+        assert_eq!(distribution.max_length(), 5)
+    }
+    #[test]
+    fn corpus_word_lengths_are_counted_without_punctuation() {
+        let distribution = WordLengthDistribution::fitted_to("Ein Hund, ein Baum. Größe!").unwrap();
+        let expected: BTreeMap<u8, u32> = vec![(3, 2), (4, 2), (5, 1)].into_iter().collect();
+        assert_eq!(distribution, WordLengthDistribution::Frequencies(expected));
+        assert_eq!(distribution.max_length(), 5);
+        assert_eq!(WordLengthDistribution::fitted_to(" ... "), None)
+    }
+    #[test]
+    fn only_lengths_of_the_corpus_are_drawn() {
+        let distribution = WordLengthDistribution::fitted_to("a bb a bb cccccc").unwrap();
+        let rng = &mut ChaCha8Rng::seed_from_u64(3);
+        assert!((0..100)
+            .map(|_| distribution.sample(rng))
+            .all(|length| [1, 2, 6].contains(&length)))
+    }
+}
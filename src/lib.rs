@@ -0,0 +1,41 @@
+//! Engine of the keyboard layout trainer: lesson generation, training
+//! sessions, statistics and the save file. It can drive typing sessions
+//! without the terminal UI, which lives in `ui` and is used by the `trainer`
+//! binary. `ui` is only built with one of the `termion` or `crossterm`
+//! features.
+//!
+//! ```
+//! use keyboard_layout_lessons::{Lesson, TrainingSession, WeightingStrategy};
+//!
+//! let lesson = Lesson::from_chars(
+//!     String::from("Home row"),
+//!     &['a', 's', 'd', 'f'],
+//!     20,
+//!     4,
+//!     WeightingStrategy::EqualWeight,
+//! );
+//! let mut session = TrainingSession::new(lesson.generate_lesson_content());
+//! while let Some(next) = session.current_char().map(String::from) {
+//!     next.chars().for_each(|typed| session.handle_key(typed));
+//! }
+//! assert!(session.is_finished());
+//! assert_eq!(session.stats().errors.total_error_count, 0);
+//! ```
+// only the UI uses the list comprehensions
+#[cfg(feature = "ui")]
+#[macro_use]
+extern crate cute;
+
+pub mod app;
+pub mod core;
+#[cfg(feature = "ui")]
+pub mod ui;
+pub mod wrapper;
+
+pub use crate::app::persistence::{load_lesson_list, save_lesson_list};
+pub use crate::app::selectable_session_list::SelectableLessonList;
+pub use crate::app::training_session::{Segment, SegmentState, TrainingSession};
+pub use crate::core::layout::KeyboardLayout;
+pub use crate::core::lesson::Lesson;
+pub use crate::core::stats::{TrainingRecord, TrainingStatistics};
+pub use crate::core::weighting_strategy::WeightingStrategy;
@@ -1,55 +1,102 @@
-use std::io;
-use std::io::Stdout;
+use std::fs::File;
 use std::time::Duration;
 
-use termion::{event::Key, raw::IntoRawMode, raw::RawTerminal};
-use tui::backend::TermionBackend;
-use tui::Terminal;
+use anyhow::Context;
+use chrono::Utc;
+use keyboard_layout_lessons::app::trainer::TrainerApp;
+use keyboard_layout_lessons::core::enums::{AppState, Key, OptionalInput};
+use keyboard_layout_lessons::core::input::InputEvent;
+use keyboard_layout_lessons::core::layout::KeyboardLayout;
+use keyboard_layout_lessons::ui::backend::{install_panic_hook, TerminalGuard};
+use keyboard_layout_lessons::ui::events::Events;
+use keyboard_layout_lessons::ui::input_translation::{
+    LayoutEmulation, NoTranslation, TranslateInput,
+};
+use keyboard_layout_lessons::ui::rendering::draw;
+use keyboard_layout_lessons::ui::theme::Theme;
+use simplelog::{Config, LevelFilter, WriteLogger};
 
-use crate::app::trainer::TrainerApp;
-use crate::core::enums::AppState;
-use crate::ui::rendering::draw;
-
-use self::core::enums::OptionalInput;
-use crate::ui::events::Events;
-#[macro_use]
-extern crate cute;
-
-mod app;
-mod core;
-mod ui;
-mod wrapper;
-
-fn start_terminal_gui(mut app: TrainerApp) -> Result<(), io::Error> {
-    let mut terminal = create_initialized_terminal()?;
+fn start_terminal_gui(
+    mut app: TrainerApp,
+    translation: Box<dyn TranslateInput>,
+    theme: Theme,
+) -> anyhow::Result<()> {
+    let mut terminal = TerminalGuard::new()?;
     // Setup event
-    let events = Events::new(Duration::new(0, 500));
+    let events = Events::with_translation(Duration::from_millis(100), translation);
     loop {
-        terminal.draw(|f| draw(f, &app))?;
-        let event = events.next().unwrap_or(OptionalInput::InputKey(Key::Esc));
+        terminal.draw(|f| draw(f, &app, &theme))?;
+        let event = events
+            .next()
+            .unwrap_or_else(|_| OptionalInput::InputKey(InputEvent::new(Key::Esc, Utc::now())));
 
         app.tick(event);
         if app.state() == &AppState::Terminated {
-            app.save();
-            break;
+            return app.save().context("your progress could not be saved");
         }
     }
-    terminal.show_cursor()?;
-    Ok(())
 }
 
-fn create_initialized_terminal() -> Result<Terminal<TermionBackend<RawTerminal<Stdout>>>, io::Error>
-{
-    let stdout = io::stdout().into_raw_mode()?;
-    let backend = TermionBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    terminal.clear()?;
-    terminal.hide_cursor()?;
-    Ok(terminal)
+/// `--emulate <layout>` treats the keyboard as the given physical layout
+/// (e.g. qwertz) and remaps typed keys to the layout of the lessons.
+fn create_input_translation(app: &TrainerApp) -> anyhow::Result<Box<dyn TranslateInput>> {
+    let args: Vec<String> = std::env::args().collect();
+    let physical_layout_name = match args.iter().position(|arg| arg == "--emulate") {
+        Some(index) => args
+            .get(index + 1)
+            .ok_or_else(|| anyhow::anyhow!("--emulate requires a physical layout name"))?,
+        None => return Ok(Box::new(NoTranslation)),
+    };
+    let physical_layout = KeyboardLayout::by_name(physical_layout_name)
+        .ok_or_else(|| anyhow::anyhow!("unknown layout {}", physical_layout_name))?;
+    let target_layout = app
+        .lesson_list
+        .layout()
+        .ok_or_else(|| anyhow::anyhow!("the lessons do not define a layout to emulate"))?;
+    Ok(Box::new(LayoutEmulation::new(
+        physical_layout,
+        target_layout.clone(),
+    )))
+}
+
+/// Diagnostics go to `trainer.log`, the terminal belongs to the UI.
+fn init_logging() {
+    let result = File::create("trainer.log")
+        .map_err(anyhow::Error::from)
+        .and_then(|file| {
+            Ok(WriteLogger::init(
+                LevelFilter::Info,
+                Config::default(),
+                file,
+            )?)
+        });
+    if let Err(error) = result {
+        eprintln!(
+            "logging is off, trainer.log could not be created: {}",
+            error
+        );
+    }
 }
 
 fn main() -> anyhow::Result<()> {
-    let mut app = TrainerApp::load(String::from("save.json"))?;
-    start_terminal_gui(app)?;
-    Ok(())
+    init_logging();
+    install_panic_hook();
+    let mut app = TrainerApp::load("save.json");
+    let translation = create_input_translation(&app)?;
+    let theme = match Theme::load("theme.json") {
+        Ok(theme) => theme,
+        Err(error) => {
+            log::warn!("theme.json is invalid: {:#}", error);
+            app.notify(format!(
+                "theme.json is invalid, using the default theme: {:#}",
+                error
+            ));
+            Theme::default()
+        }
+    };
+    let result = start_terminal_gui(app, translation, theme.for_terminal());
+    if let Err(error) = &result {
+        log::error!("{:#}", error);
+    }
+    result
 }
@@ -0,0 +1,118 @@
+use std::io;
+use std::io::Stdout;
+
+use crossterm::cursor::Show;
+use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use tui::backend::CrosstermBackend;
+use tui::Terminal;
+
+use crate::core::enums::{Key, OptionalInput};
+use crate::core::input::{InputEvent, Modifiers};
+#[cfg(not(test))]
+use crate::wrapper::clock::Clock;
+#[cfg(test)]
+use crate::wrapper::fake_clock::FakeClock as Clock;
+
+pub type TerminalBackend = CrosstermBackend<Stdout>;
+
+pub fn create_terminal() -> Result<Terminal<TerminalBackend>, io::Error> {
+    enable_raw_mode().map_err(to_io_error)?;
+    let backend = CrosstermBackend::new(io::stdout());
+    let mut terminal = Terminal::new(backend)?;
+    terminal.clear()?;
+    terminal.hide_cursor()?;
+    Ok(terminal)
+}
+
+pub fn restore_terminal(terminal: &mut Terminal<TerminalBackend>) -> Result<(), io::Error> {
+    terminal.show_cursor()?;
+    disable_raw_mode().map_err(to_io_error)
+}
+
+/// Reads terminal events until reading fails. Keys are passed on as backend
+/// neutral events stamped with the time they were read, resizes as `Resize`
+/// and everything else as `NoInput`.
+pub fn read_input(mut handle_input: impl FnMut(OptionalInput)) {
+    while let Ok(event) = read() {
+        match event {
+            Event::Key(key_event) => handle_input(OptionalInput::InputKey(map_event(key_event))),
+            Event::Resize(_, _) => handle_input(OptionalInput::Resize),
+            _ => handle_input(OptionalInput::NoInput),
+        }
+    }
+}
+
+/// Leaves raw mode and shows the cursor without access to the terminal.
+pub fn restore_after_panic() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), Show);
+}
+
+fn map_event(key_event: KeyEvent) -> InputEvent {
+    let modifiers = Modifiers {
+        shift: key_event.modifiers.contains(KeyModifiers::SHIFT),
+        ctrl: key_event.modifiers.contains(KeyModifiers::CONTROL),
+        alt: key_event.modifiers.contains(KeyModifiers::ALT),
+    };
+    InputEvent::new(map_key(key_event), Clock::now()).with_modifiers(modifiers)
+}
+
+fn map_key(key_event: KeyEvent) -> Key {
+    let modifiers = key_event.modifiers;
+    match key_event.code {
+        // AltGr is reported as Ctrl+Alt on some platforms
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+            Key::Char(c)
+        }
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => Key::Ctrl(c),
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::ALT) => Key::Alt(c),
+        KeyCode::Char(c) => Key::Char(c),
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Esc => Key::Esc,
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        _ => Key::Other,
+    }
+}
+
+fn to_io_error(error: crossterm::ErrorKind) -> io::Error {
+    io::Error::other(error.to_string())
+}
+
+#[cfg(test)]
+mod test_crossterm_backend {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn modifiers_are_mapped() {
+        let ctrl_k = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert_eq!(map_key(ctrl_k), Key::Ctrl('k'));
+        let shifted = KeyEvent::new(KeyCode::Char('K'), KeyModifiers::SHIFT);
+        assert_eq!(map_key(shifted), Key::Char('K'))
+    }
+    #[test]
+    fn alt_gr_characters_are_typed() {
+        let alt_gr = KeyEvent::new(
+            KeyCode::Char('@'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        );
+        assert_eq!(map_key(alt_gr), Key::Char('@'))
+    }
+    #[test]
+    fn events_keep_modifiers_and_read_time() {
+        let now = Clock::now();
+        let shifted = map_event(KeyEvent::new(KeyCode::Char('K'), KeyModifiers::SHIFT));
+        assert_eq!(shifted.key, Key::Char('K'));
+        assert!(shifted.modifiers.shift);
+        assert!(!shifted.modifiers.ctrl);
+        assert_eq!(shifted.timestamp, now)
+    }
+}
@@ -0,0 +1,68 @@
+//! The terminal library behind the UI, chosen with the `termion` (default)
+//! or `crossterm` cargo feature. Both provide the same functions.
+
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::panic;
+
+use tui::Terminal;
+
+#[cfg(feature = "crossterm")]
+mod crossterm_backend;
+#[cfg(all(feature = "termion", not(feature = "crossterm")))]
+mod termion_backend;
+
+#[cfg(feature = "crossterm")]
+pub use self::crossterm_backend::{
+    create_terminal, read_input, restore_after_panic, restore_terminal, TerminalBackend,
+};
+#[cfg(all(feature = "termion", not(feature = "crossterm")))]
+pub use self::termion_backend::{
+    create_terminal, read_input, restore_after_panic, restore_terminal, TerminalBackend,
+};
+
+/// The terminal set up for the UI. It is restored when the guard is dropped,
+/// also when leaving because of an error or a panic.
+pub struct TerminalGuard {
+    terminal: Terminal<TerminalBackend>,
+}
+
+impl TerminalGuard {
+    pub fn new() -> Result<TerminalGuard, io::Error> {
+        Ok(TerminalGuard {
+            terminal: create_terminal()?,
+        })
+    }
+}
+
+impl Deref for TerminalGuard {
+    type Target = Terminal<TerminalBackend>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if let Err(error) = restore_terminal(&mut self.terminal) {
+            log::error!("restoring the terminal failed: {}", error);
+        }
+    }
+}
+
+/// Restores the terminal before a panic message is printed and logs the
+/// panic. The message ends lines with `\r\n` as raw mode may still be on.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|panic_info| {
+        restore_after_panic();
+        log::error!("{}", panic_info);
+        eprint!("{}\r\n", panic_info.to_string().replace('\n', "\r\n"));
+    }));
+}
@@ -0,0 +1,92 @@
+use std::io;
+use std::io::{stdin, Stdout, Write};
+
+use termion::event::{Event, Key as TermionKey};
+use termion::input::TermRead;
+use termion::raw::{IntoRawMode, RawTerminal};
+use tui::backend::TermionBackend;
+use tui::Terminal;
+
+use crate::core::enums::{Key, OptionalInput};
+use crate::core::input::InputEvent;
+#[cfg(not(test))]
+use crate::wrapper::clock::Clock;
+#[cfg(test)]
+use crate::wrapper::fake_clock::FakeClock as Clock;
+
+pub type TerminalBackend = TermionBackend<RawTerminal<Stdout>>;
+
+pub fn create_terminal() -> Result<Terminal<TerminalBackend>, io::Error> {
+    let stdout = io::stdout().into_raw_mode()?;
+    let backend = TermionBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.clear()?;
+    terminal.hide_cursor()?;
+    Ok(terminal)
+}
+
+/// Raw mode ends when the terminal is dropped.
+pub fn restore_terminal(terminal: &mut Terminal<TerminalBackend>) -> Result<(), io::Error> {
+    terminal.show_cursor()
+}
+
+/// Shows the cursor again. Raw mode ends when the terminal is dropped while
+/// unwinding.
+pub fn restore_after_panic() {
+    let mut stdout = io::stdout();
+    let _ = write!(stdout, "{}", termion::cursor::Show);
+    let _ = stdout.flush();
+}
+
+/// Reads from stdin until it is closed. Keys are passed on as backend
+/// neutral events stamped with the time they were read, everything else as
+/// `NoInput`. Termion has no Shift modifier, shifted characters arrive as
+/// such. It does not report resizes either, the next tick redraws them.
+pub fn read_input(mut handle_input: impl FnMut(OptionalInput)) {
+    for event_res in stdin().events() {
+        match event_res {
+            Ok(Event::Key(key)) => handle_input(OptionalInput::InputKey(InputEvent::new(
+                map_key(key),
+                Clock::now(),
+            ))),
+            _ => handle_input(OptionalInput::NoInput),
+        }
+    }
+}
+
+fn map_key(key: TermionKey) -> Key {
+    match key {
+        TermionKey::Char('\n') => Key::Enter,
+        TermionKey::Char('\t') => Key::Tab,
+        TermionKey::Char(c) => Key::Char(c),
+        TermionKey::Ctrl(c) => Key::Ctrl(c),
+        TermionKey::Alt(c) => Key::Alt(c),
+        TermionKey::Backspace => Key::Backspace,
+        TermionKey::Esc => Key::Esc,
+        TermionKey::Up => Key::Up,
+        TermionKey::Down => Key::Down,
+        TermionKey::Left => Key::Left,
+        TermionKey::Right => Key::Right,
+        _ => Key::Other,
+    }
+}
+
+#[cfg(test)]
+mod test_termion_backend {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn enter_and_tab_are_not_characters() {
+        assert_eq!(map_key(TermionKey::Char('\n')), Key::Enter);
+        assert_eq!(map_key(TermionKey::Char('\t')), Key::Tab);
+        assert_eq!(map_key(TermionKey::Char('ß')), Key::Char('ß'))
+    }
+    #[test]
+    fn modifiers_and_special_keys_are_kept() {
+        assert_eq!(map_key(TermionKey::Ctrl('k')), Key::Ctrl('k'));
+        assert_eq!(map_key(TermionKey::Esc), Key::Esc);
+        assert_eq!(map_key(TermionKey::F(1)), Key::Other)
+    }
+}
@@ -1,49 +1,105 @@
-use std::io::stdin;
-use std::sync::mpsc::{channel, Receiver, RecvError, Sender};
+use std::sync::mpsc::{channel, Receiver, RecvError, RecvTimeoutError, Sender};
 use std::thread;
+use std::thread::JoinHandle;
 use std::time::Duration;
 
-use termion::event::Event;
-use termion::input::TermRead;
-
 use crate::core::enums::OptionalInput;
+use crate::ui::backend;
+use crate::ui::input_translation::{NoTranslation, TranslateInput};
 
 pub struct Events {
     rx: Receiver<OptionalInput>,
     _tx: Sender<OptionalInput>,
+    _ticker: Ticker,
 }
 
 impl Events {
     pub fn new(tick_rate: Duration) -> Events {
+        Events::with_translation(tick_rate, Box::new(NoTranslation))
+    }
+
+    /// Passes every key read from the terminal through `translation` before
+    /// it reaches the app. Between keys a `NoInput` tick is sent every
+    /// `tick_rate`, so the UI keeps redrawing, e.g. after the terminal was
+    /// resized.
+    pub fn with_translation(tick_rate: Duration, translation: Box<dyn TranslateInput>) -> Events {
         let (tx, rx) = channel();
 
-        let event_tx = tx.clone(); // the thread::spawn own event_tx
+        // the thread::spawn own event_tx
+        let event_tx = tx.clone();
+        // The input thread blocks on reading the terminal, so it is not
+        // joined. It ends with the process.
         thread::spawn(move || {
-            for event_res in stdin().events() {
-                match event_res {
-                    Ok(event) => {
-                        match event {
-                            Event::Key(key) => {
-                                // info!("event: {:?}", event);
-                                event_tx.send(OptionalInput::InputKey(key)).unwrap();
-                            }
-                            _ => {
-                                event_tx.send(OptionalInput::NoInput).unwrap();
-                            }
-                        }
+            backend::read_input(|input| {
+                let input = match input {
+                    OptionalInput::InputKey(event) => {
+                        OptionalInput::InputKey(event.remapped(translation.translate(event.key)))
                     }
-                    Err(_) => {
-                        event_tx.send(OptionalInput::NoInput).unwrap();
-                        // error!("{:?}", err);
-                    }
-                }
-            }
+                    other => other,
+                };
+                // nobody listens anymore once the app is shutting down
+                let _ = event_tx.send(input);
+            });
         });
 
-        Events { rx, _tx: tx }
+        Events {
+            rx,
+            _ticker: Ticker::start(tick_rate, tx.clone()),
+            _tx: tx,
+        }
     }
 
     pub fn next(&self) -> Result<OptionalInput, RecvError> {
         self.rx.recv()
     }
 }
+
+/// Sends a `NoInput` tick every `tick_rate` until it is dropped. Dropping
+/// stops the thread right away and waits for it.
+struct Ticker {
+    stop_tx: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Ticker {
+    fn start(tick_rate: Duration, tick_tx: Sender<OptionalInput>) -> Ticker {
+        let (stop_tx, stop_rx) = channel();
+        let handle = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(tick_rate) {
+                if tick_tx.send(OptionalInput::NoInput).is_err() {
+                    break;
+                }
+            }
+        });
+        Ticker {
+            stop_tx: Some(stop_tx),
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        // disconnecting the channel ends the wait of the thread
+        self.stop_tx.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_events {
+    use super::*;
+
+    #[test]
+    fn ticker_sends_ticks_until_dropped() {
+        let (tx, rx) = channel();
+        let ticker = Ticker::start(Duration::from_millis(1), tx);
+        assert!(matches!(rx.recv(), Ok(OptionalInput::NoInput)));
+        drop(ticker);
+        // the thread has ended and dropped its sender
+        while let Ok(OptionalInput::NoInput) = rx.recv() {}
+        assert!(rx.try_recv().is_err())
+    }
+}
@@ -0,0 +1,111 @@
+use crate::core::enums::Key;
+use crate::core::layout::KeyboardLayout;
+
+/// A stage between reading keys from the terminal and handing them to the
+/// app, e.g. to emulate a keyboard layout the OS is not configured for.
+pub trait TranslateInput: Send {
+    fn translate(&self, key: Key) -> Key;
+}
+
+/// Passes all keys on unchanged.
+pub struct NoTranslation;
+
+impl TranslateInput for NoTranslation {
+    fn translate(&self, key: Key) -> Key {
+        key
+    }
+}
+
+/// Interprets typed characters as key positions on the `physical` layout and
+/// replaces them with the character at the same position and layer of the
+/// `target` layout. Characters that cannot be mapped are passed on unchanged.
+pub struct LayoutEmulation {
+    physical: KeyboardLayout,
+    target: KeyboardLayout,
+}
+
+impl LayoutEmulation {
+    pub fn new(physical: KeyboardLayout, target: KeyboardLayout) -> LayoutEmulation {
+        LayoutEmulation { physical, target }
+    }
+
+    pub fn reversed(&self) -> LayoutEmulation {
+        LayoutEmulation::new(self.target.clone(), self.physical.clone())
+    }
+
+    pub fn translate_char(&self, typed: char) -> char {
+        self.physical
+            .find(&typed.to_string())
+            .and_then(|(position, layer)| self.target.character_at(position, layer))
+            .unwrap_or(typed)
+    }
+}
+
+impl TranslateInput for LayoutEmulation {
+    fn translate(&self, key: Key) -> Key {
+        match key {
+            Key::Char(c) => Key::Char(self.translate_char(c)),
+            key => key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_input_translation {
+    use pretty_assertions::assert_eq;
+
+    use crate::core::layout::Layer;
+
+    use super::*;
+
+    fn qwertz_to_bone() -> LayoutEmulation {
+        LayoutEmulation::new(KeyboardLayout::qwertz(), KeyboardLayout::bone())
+    }
+
+    #[test]
+    fn home_row_is_remapped_by_position() {
+        let emulation = qwertz_to_bone();
+        let typed: String = "asdfghjklöä"
+            .chars()
+            .map(|c| emulation.translate_char(c))
+            .collect();
+        assert_eq!(typed, "ctieobnrsgq")
+    }
+    #[test]
+    fn shift_layer_is_kept() {
+        assert_eq!(qwertz_to_bone().translate(Key::Char('F')), Key::Char('E'))
+    }
+    #[test]
+    fn unmapped_keys_are_passed_on() {
+        let emulation = qwertz_to_bone();
+        assert_eq!(emulation.translate(Key::Enter), Key::Enter);
+        assert_eq!(emulation.translate(Key::Esc), Key::Esc);
+        assert_eq!(emulation.translate(Key::Ctrl('a')), Key::Ctrl('a'));
+    }
+    #[test]
+    fn round_trip_restores_physical_keys() {
+        let emulation = qwertz_to_bone();
+        let reversed = emulation.reversed();
+        for key in KeyboardLayout::qwertz().keys() {
+            for layer in [Layer::Base, Layer::Shift].iter() {
+                let physical = match key.character(*layer) {
+                    Some(physical) => physical,
+                    None => continue,
+                };
+                let emulated = emulation.translate_char(physical);
+                if emulated != physical {
+                    assert_eq!(reversed.translate_char(emulated), physical);
+                }
+            }
+        }
+    }
+    #[test]
+    fn round_trip_between_qwerty_and_qwertz() {
+        let emulation = LayoutEmulation::new(KeyboardLayout::qwerty(), KeyboardLayout::qwertz());
+        let reversed = emulation.reversed();
+        for c in "qwertyuiopasdfghjklzxcvbnm".chars() {
+            assert_eq!(reversed.translate_char(emulation.translate_char(c)), c);
+        }
+        assert_eq!(emulation.translate_char('y'), 'z');
+    }
+}
@@ -1,2 +1,5 @@
+pub mod backend;
 pub mod events;
+pub mod input_translation;
 pub mod rendering;
+pub mod theme;
@@ -1,79 +1,762 @@
+use chrono::Datelike;
 use tui::backend::Backend;
 use tui::layout::{Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Modifier, Style};
-use tui::text::Span;
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans, Text};
 use tui::widgets::{
     Axis, Block, Borders, Chart, Dataset, Gauge, GraphType, List, ListItem, ListState, Paragraph,
-    Row, Table,
+    Row, Table, Wrap,
 };
 use tui::{symbols, Frame};
 
+use crate::app::lesson_editor::{DraftField, DraftOrigin, LessonDraft};
+use crate::app::practice_planner::PlannedSession;
+use crate::app::session_summary::{RoutineSummary, SessionSummary};
 use crate::app::trainer::TrainerApp;
+use crate::app::training_session::{Segment, SegmentState};
+use crate::core::achievements::Achievement;
+use crate::core::character::Character;
 use crate::core::enums::AppState;
+use crate::core::ghost::{describe_lead, Ghost};
+use crate::core::goals::goal_history;
+use crate::core::layout::{KeyPosition, KeyboardLayout, Layer};
+use crate::core::lesson::lesson_of_the_day_seed;
+use crate::core::mastery::LessonState;
 use crate::core::stats::TrainingRecord;
+use crate::core::weighting_strategy::WeightingStrategy;
+use crate::ui::theme::Theme;
+#[cfg(not(test))]
+use crate::wrapper::clock::Clock;
+#[cfg(test)]
+use crate::wrapper::fake_clock::FakeClock as Clock;
 
-pub fn draw<B: Backend>(f: &mut Frame<B>, app: &TrainerApp)
+pub fn draw<B>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme)
 where
     B: Backend,
 {
+    let mut area = f.size();
+    if let Some(seed_input) = app.seed_input() {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(10), Constraint::Length(3)].as_ref())
+            .split(area);
+        area = rows[0];
+        draw_seed_input(f, seed_input, theme, rows[1]);
+    }
+    if let Some(notification) = app.notification() {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(10), Constraint::Length(4)].as_ref())
+            .split(area);
+        area = rows[0];
+        draw_notification(f, notification, theme, rows[1]);
+    }
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .margin(1)
         .constraints([Constraint::Length(20), Constraint::Min(50)].as_ref())
-        .split(f.size());
-    draw_lesson_selection(f, app, chunks[0]);
+        .split(area);
+    draw_left_column(f, app, theme, chunks[0]);
+
+    match app.state() {
+        AppState::Planner => draw_practice_plan(f, app, theme, chunks[1]),
+        AppState::Profile => draw_profile(f, app, theme, chunks[1]),
+        AppState::Goals => draw_goals(f, app, theme, chunks[1]),
+        AppState::LessonEditor => match app.lesson_draft() {
+            Some(draft) => draw_lesson_draft(f, app, draft, theme, chunks[1]),
+            None => draw_lesson_overview(f, app, theme, chunks[1]),
+        },
+        _ => draw_lesson_details(f, app, theme, chunks[1]),
+    }
+}
+
+/// A recoverable failure, shown until the next key press.
+fn draw_notification<B: Backend>(f: &mut Frame<B>, notification: &str, theme: &Theme, area: Rect) {
+    let notification_widget = Paragraph::new(notification)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Notice (any key to dismiss)"),
+        )
+        .style(theme.wrong_style())
+        .wrap(Wrap { trim: true });
+    f.render_widget(notification_widget, area);
+}
+
+fn draw_seed_input<B: Backend>(f: &mut Frame<B>, seed_input: &str, theme: &Theme, area: Rect) {
+    let seed_widget = Paragraph::new(seed_input)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Seed (Enter: type the selected lesson, Esc: cancel)"),
+        )
+        .style(theme.active_style());
+    f.render_widget(seed_widget, area);
+}
+
+fn draw_profile<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme, area: Rect) {
+    let style = theme.active_style();
+    let lesson_list = &app.lesson_list;
+    let streak = lesson_list.practice_streak(Clock::now().naive_utc().date());
+    let overall_best = match lesson_list.overall_personal_best() {
+        Some((index, words_per_minute)) => format!(
+            "{} WPM in {}",
+            words_per_minute,
+            app.lessons()[index].name()
+        ),
+        None => String::from("-"),
+    };
+    let mut lines = vec![
+        Spans::from(format!("Personal best:  {}", overall_best)),
+        Spans::from(format!(
+            "Streak:         {} days (longest {} days)",
+            streak.current_days, streak.longest_days
+        )),
+        Spans::from(""),
+        Spans::from(Span::styled(
+            "Personal bests",
+            style.add_modifier(Modifier::BOLD),
+        )),
+    ];
+    for (index, lesson) in app.lessons().iter().enumerate() {
+        if let Some(words_per_minute) = lesson_list.personal_best(index) {
+            lines.push(Spans::from(format!(
+                "  {:<16}{} WPM",
+                lesson.name(),
+                words_per_minute
+            )));
+        }
+    }
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        "Achievements",
+        style.add_modifier(Modifier::BOLD),
+    )));
+    for achievement in lesson_list.profile().achievements.iter() {
+        lines.push(Spans::from(format!(
+            "  {}  {}",
+            achievement.achieved_at.format("%Y-%m-%d"),
+            describe_achievement(achievement)
+        )));
+    }
+    let profile_widget = Paragraph::new(lines)
+        .block(Block::default().title("Profile").borders(Borders::ALL))
+        .style(style);
+    f.render_widget(profile_widget, area);
+}
+
+fn describe_achievement(achievement: &Achievement) -> String {
+    match &achievement.layout {
+        Some(layout) => format!("{} on {}", achievement.kind.description(), layout),
+        None => achievement.kind.description().to_string(),
+    }
+}
+
+fn draw_practice_plan<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme, area: Rect) {
+    let style = theme.active_style();
+    let now = Clock::now();
+    let mut items: Vec<ListItem> = app
+        .practice_plan()
+        .iter()
+        .map(|planned_session| {
+            let description = match planned_session {
+                PlannedSession::Review { lesson_index, due } => {
+                    let overdue_days = (now - *due).num_days();
+                    let overdue = match overdue_days {
+                        0 => String::from("due today"),
+                        1 => String::from("due since yesterday"),
+                        days => format!("due since {} days", days),
+                    };
+                    format!(
+                        "Review  {} ({})",
+                        app.lessons()[*lesson_index].name(),
+                        overdue
+                    )
+                }
+                PlannedSession::NewLesson { lesson_index } => {
+                    format!("New     {}", app.lessons()[*lesson_index].name())
+                }
+                PlannedSession::WeakKeyDrill { lesson } => format!("Drill   {}", lesson.name()),
+            };
+            ListItem::new(Span::raw(description))
+        })
+        .collect();
+    items.push(ListItem::new(Span::raw("")));
+    items.push(ListItem::new(Span::styled(
+        "Enter: start the planned sessions   Esc: skip",
+        theme.inactive_style(),
+    )));
+    let plan_widget = List::new(items)
+        .block(Block::default().title("Today's plan").borders(Borders::ALL))
+        .style(style);
+    f.render_widget(plan_widget, area);
+}
 
-    draw_lesson_details(f, app, chunks[1]);
+fn draw_left_column<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme, area: Rect) {
+    let daily_goal = match app.lesson_list.daily_goal() {
+        Some(daily_goal) => daily_goal,
+        None => return draw_lesson_selection(f, app, theme, area),
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+        .split(area);
+    draw_lesson_selection(f, app, theme, chunks[0]);
+
+    let today = Clock::now().naive_utc().date();
+    let practice = app
+        .lesson_list
+        .daily_practice()
+        .remove(&today)
+        .unwrap_or_default();
+    let goal_widget = Gauge::default()
+        .block(Block::default().title("Today").borders(Borders::ALL))
+        .gauge_style(theme.active_style().add_modifier(Modifier::BOLD))
+        .label(daily_goal.describe_progress(&practice))
+        .ratio(daily_goal.progress(&practice));
+    f.render_widget(goal_widget, chunks[1]);
 }
 
-fn draw_lesson_selection<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, area: Rect) {
-    let items: Vec<ListItem> = app
+/// Number of weeks shown in the goal calendar, including the current one.
+const GOAL_CALENDAR_WEEKS: i64 = 6;
+
+fn draw_goals<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme, area: Rect) {
+    let style = theme.active_style();
+    let lesson_list = &app.lesson_list;
+    let total_minutes = lesson_list.total_practice_seconds() / 60;
+    let mut lines = vec![
+        Spans::from(format!(
+            "Total practice time:  {} h {} min",
+            total_minutes / 60,
+            total_minutes % 60
+        )),
+        Spans::from(""),
+    ];
+    let daily_goal = match lesson_list.daily_goal() {
+        Some(daily_goal) => daily_goal,
+        None => {
+            lines.push(Spans::from("No daily goal set. Press t to set one."));
+            let goals_widget = Paragraph::new(lines)
+                .block(Block::default().title("Goals").borders(Borders::ALL))
+                .style(style);
+            return f.render_widget(goals_widget, area);
+        }
+    };
+    let today = Clock::now().naive_utc().date();
+    let practice = lesson_list.daily_practice();
+    let today_practice = practice.get(&today).copied().unwrap_or_default();
+    lines.push(Spans::from(format!(
+        "Today:                {}",
+        daily_goal.describe_progress(&today_practice)
+    )));
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        format!("Last {} weeks", GOAL_CALENDAR_WEEKS),
+        style.add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Spans::from("          Mo Tu We Th Fr Sa Su"));
+
+    let first_day = today
+        - chrono::Duration::days(
+            today.weekday().num_days_from_monday() as i64 + 7 * (GOAL_CALENDAR_WEEKS - 1),
+        );
+    let history = goal_history(&practice, daily_goal, first_day, today);
+    for week in history.chunks(7) {
+        let mut spans = vec![Span::raw(format!("  {}", week[0].0.format("%m-%d")))];
+        for (_, progress) in week {
+            spans.push(Span::raw("  "));
+            spans.push(match *progress {
+                p if p >= 1.0 => Span::styled("■", theme.goal_reached_style()),
+                p if p > 0.0 => Span::styled("□", theme.goal_partial_style()),
+                _ => Span::styled("·", theme.inactive_style()),
+            });
+        }
+        lines.push(Spans::from(spans));
+    }
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(
+        "t: change goal   +/-: adjust target   x: remove goal",
+    ));
+    let goals_widget = Paragraph::new(lines)
+        .block(Block::default().title("Goals").borders(Borders::ALL))
+        .style(style);
+    f.render_widget(goals_widget, area);
+}
+
+fn join_keys(keys: &[Character]) -> String {
+    keys.iter()
+        .map(|key| key.value.as_str())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn describe_weighting(weighting_strategy: &WeightingStrategy) -> String {
+    match weighting_strategy {
+        WeightingStrategy::EqualWeight => String::from("equal"),
+        WeightingStrategy::FocusKey(focus_key) => format!(
+            "{} {}x as often",
+            join_keys(&focus_key.focused_keys),
+            focus_key.weight
+        ),
+    }
+}
+
+/// The selected lesson and what can be done with it in the editor.
+fn draw_lesson_overview<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme, area: Rect) {
+    let style = theme.active_style();
+    let selected_lesson = app
+        .lesson_list
+        .selected_index()
+        .and_then(|index| Some((index, app.lessons().get(index)?)));
+    let mut lines = match selected_lesson {
+        Some((index, lesson)) => {
+            let inherited_keys = &lesson.keys()[..lesson.keys().len() - lesson.new_keys().len()];
+            let mut lines = vec![
+                Spans::from(Span::styled(
+                    lesson.name().to_string(),
+                    style.add_modifier(Modifier::BOLD),
+                )),
+                Spans::from(""),
+                Spans::from(format!("Inherited keys: {}", join_keys(inherited_keys))),
+                Spans::from(format!("New keys:       {}", join_keys(lesson.new_keys()))),
+                Spans::from(format!(
+                    "Length:         {} characters, words of {}",
+                    lesson.lesson_length(),
+                    lesson.word_length()
+                )),
+                Spans::from(format!(
+                    "Weighting:      {}",
+                    describe_weighting(lesson.weighting_strategy())
+                )),
+                Spans::from(format!(
+                    "Sessions:       {}",
+                    app.lesson_list.lesson_records(index).len()
+                )),
+            ];
+            if app.pending_deletion() == Some(index) {
+                lines.push(Spans::from(""));
+                lines.push(Spans::from(Span::styled(
+                    format!(
+                        "Delete {} and its sessions? Press x again to confirm.",
+                        lesson.name()
+                    ),
+                    theme.wrong_style(),
+                )));
+            }
+            lines
+        }
+        None => vec![Spans::from(
+            "Select a lesson to edit it or press n for a new one.",
+        )],
+    };
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        "n: new   c: derive   Enter: edit   y: duplicate   [/]: move   x: delete   Esc: save and back",
+        theme.inactive_style(),
+    )));
+    let overview_widget = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Lesson editor")
+                .borders(Borders::ALL),
+        )
+        .style(style)
+        .wrap(Wrap { trim: false });
+    f.render_widget(overview_widget, area);
+}
+
+/// The form of the edited lesson above the layout its keys are picked from.
+fn draw_lesson_draft<B: Backend>(
+    f: &mut Frame<B>,
+    app: &TrainerApp,
+    draft: &LessonDraft,
+    theme: &Theme,
+    area: Rect,
+) {
+    let style = theme.active_style();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(11), Constraint::Min(0)].as_ref())
+        .split(area);
+    let mut lines: Vec<Spans> = DraftField::ALL
+        .iter()
+        .map(|field| {
+            let marker = if *field == draft.field { "> " } else { "  " };
+            let mut spans = vec![Span::raw(format!("{}{:<13}", marker, field.name()))];
+            match field {
+                DraftField::Name if draft.field == DraftField::Name => {
+                    spans.push(Span::raw(format!("{}_", draft.name)))
+                }
+                DraftField::Name => spans.push(Span::raw(draft.name.clone())),
+                DraftField::Keys => {
+                    if !draft.inherited_keys.is_empty() {
+                        spans.push(Span::styled(
+                            format!("{} ", join_keys(&draft.inherited_keys)),
+                            theme.typed_style(),
+                        ));
+                    }
+                    spans.push(Span::styled(join_keys(&draft.keys), theme.next_key_style()));
+                }
+                DraftField::LessonLength => {
+                    spans.push(Span::raw(format!("{} characters", draft.lesson_length)))
+                }
+                DraftField::WordLength => match draft.distribution_max_length {
+                    Some(max_length) => spans.push(Span::styled(
+                        format!("up to {} from the save file's word lengths", max_length),
+                        theme.inactive_style(),
+                    )),
+                    None => spans.push(Span::raw(draft.word_length.to_string())),
+                },
+                DraftField::Weighting => match &draft.kept_weighting {
+                    Some(weighting_strategy) => {
+                        spans.push(Span::raw(describe_weighting(weighting_strategy)))
+                    }
+                    None => spans.push(Span::raw(draft.describe_weighting())),
+                },
+            }
+            Spans::from(spans)
+        })
+        .collect();
+    let field_hint = match draft.field {
+        DraftField::Name => "type the name",
+        DraftField::Keys => {
+            "type a key or pick it with ←/→ and Enter, Ctrl+l: layer, Backspace: remove last"
+        }
+        DraftField::WordLength if draft.distribution_max_length.is_some() => {
+            "edit word_lengths in the save file to change"
+        }
+        _ => "←/→: change",
+    };
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        field_hint,
+        theme.inactive_style(),
+    )));
+    lines.push(Spans::from(Span::styled(
+        "Tab/↑/↓: field   Enter or Ctrl+s: save   Esc: cancel",
+        theme.inactive_style(),
+    )));
+    let title = match draft.origin {
+        DraftOrigin::New => String::from("New lesson"),
+        DraftOrigin::DerivedFrom(index) => format!(
+            "New lesson derived from {}",
+            app.lessons()
+                .get(index)
+                .map(|lesson| lesson.name())
+                .unwrap_or_default()
+        ),
+        DraftOrigin::Edit(_) => String::from("Edit lesson"),
+    };
+    let form_widget = Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(style)
+        .wrap(Wrap { trim: false });
+    f.render_widget(form_widget, chunks[0]);
+
+    let layout = match app.lesson_list.layout() {
+        Some(layout) => layout,
+        None => {
+            let hint = Paragraph::new("No keyboard layout configured, type the keys instead")
+                .style(theme.inactive_style());
+            return f.render_widget(hint, chunks[1]);
+        }
+    };
+    let cursor = draft.cursor_position(layout);
+    let lines = render_keyboard_rows(layout, draft.layer, |position, label| {
+        let label = label.to_string();
+        if draft.field == DraftField::Keys && Some(position) == cursor {
+            theme.current_style()
+        } else if draft.is_picked(&label) {
+            theme.next_key_style()
+        } else if draft.is_inherited(&label) {
+            theme.typed_style()
+        } else {
+            style
+        }
+    });
+    let keyboard_widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!("{} - {}", layout.name, draft.layer.name()))
+            .borders(Borders::ALL)
+            .style(style),
+    );
+    f.render_widget(keyboard_widget, chunks[1]);
+}
+
+fn draw_lesson_selection<B: Backend>(
+    f: &mut Frame<B>,
+    app: &TrainerApp,
+    theme: &Theme,
+    area: Rect,
+) {
+    let mut items: Vec<ListItem> = app
         .lessons()
         .iter()
-        .map(|lesson| ListItem::new(Span::raw(lesson.name())))
+        .enumerate()
+        .map(
+            |(index, lesson)| match app.lesson_list.lesson_state(index) {
+                LessonState::Mastered => ListItem::new(Span::raw(format!("✓ {}", lesson.name()))),
+                LessonState::Unlocked => ListItem::new(Span::raw(format!("  {}", lesson.name()))),
+                LessonState::Locked => ListItem::new(Span::styled(
+                    format!("- {}", lesson.name()),
+                    theme.locked_style(),
+                )),
+            },
+        )
         .collect();
+    items.extend(
+        app.lesson_list
+            .routines()
+            .iter()
+            .map(|routine| ListItem::new(Span::raw(format!("» {}", routine.name)))),
+    );
 
+    let title = if app.lesson_list.auto_advance() {
+        "Lessons (auto)"
+    } else {
+        "Lessons"
+    };
+    // lessons are also chosen in the editor, unless one is being edited
+    let list_state = if app.lesson_draft().is_none() && app.state() == &AppState::LessonEditor {
+        AppState::LessonEditor
+    } else {
+        AppState::LessonSelection
+    };
     let lesson_selection = List::new(items)
-        .block(Block::default().title("Lessons").borders(Borders::ALL))
+        .block(Block::default().title(title).borders(Borders::ALL))
         .style(get_style_depending_on_app_state(
+            theme,
             app.state(),
-            AppState::LessonSelection,
+            list_state,
         ))
-        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_style(theme.highlight_style())
         .highlight_symbol(">>");
     let mut selected_lesson_state = ListState::default();
     selected_lesson_state.select(app.lesson_list.selected_index());
     f.render_stateful_widget(lesson_selection, area, &mut selected_lesson_state)
 }
 
-fn draw_lesson_details<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, area: Rect) {
+fn draw_lesson_details<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme, area: Rect) {
+    let training_ratio = if app.show_keyboard() {
+        Constraint::Ratio(2, 3)
+    } else {
+        Constraint::Ratio(1, 2)
+    };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+        .constraints([training_ratio, Constraint::Min(0)])
         .split(area);
-    draw_training(f, app, chunks[0]);
-    draw_statistics(f, app, chunks[1]);
+    match (app.routine_summary(), app.session_summary()) {
+        (Some(routine_summary), _) if app.state() == &AppState::SessionSummary => {
+            draw_routine_summary(f, routine_summary, theme, chunks[0])
+        }
+        (_, Some(summary)) if app.state() == &AppState::SessionSummary => {
+            draw_session_summary(f, summary, theme, chunks[0])
+        }
+        _ => draw_training(f, app, theme, chunks[0]),
+    }
+    draw_statistics(f, app, theme, chunks[1]);
+}
+
+fn draw_session_summary<B: Backend>(
+    f: &mut Frame<B>,
+    summary: &SessionSummary,
+    theme: &Theme,
+    area: Rect,
+) {
+    let style = theme.active_style();
+    let stats = &summary.stats;
+    let words_per_minute = stats.typing_speed.words_per_minute();
+    let personal_best = match summary.personal_best_words_per_minute {
+        Some(best) if summary.is_personal_best() => format!("new personal best (was {})", best),
+        Some(best) => format!("personal best {}", best),
+        None => String::from("first session"),
+    };
+    let rolling_average = match summary.rolling_average_words_per_minute {
+        Some(average) => format!(", average {:.1}", average),
+        None => String::new(),
+    };
+    let slowest_keys = summary
+        .slowest_keys
+        .iter()
+        .map(|(key, time)| format!("{} {} ms", key, time.num_milliseconds()))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let most_missed_keys = summary
+        .most_missed_keys
+        .iter()
+        .map(|(key, errors)| format!("{} {}x", key, errors))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let mut lines = vec![
+        Spans::from(Span::styled(
+            format!("{} finished", summary.lesson_name),
+            style.add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(""),
+        Spans::from(format!(
+            "WPM:          {} (net {}), {}{}",
+            words_per_minute,
+            stats.net_words_per_minute(),
+            personal_best,
+            rolling_average
+        )),
+        Spans::from(format!(
+            "Accuracy:     {:.1} % ({} errors)",
+            100.0 * stats.accuracy(),
+            stats.errors.total_error_count
+        )),
+        Spans::from(format!(
+            "Duration:     {}:{:02}",
+            stats.duration_seconds / 60,
+            stats.duration_seconds % 60
+        )),
+        Spans::from(format!("Slowest keys: {}", slowest_keys)),
+        Spans::from(format!("Most missed:  {}", most_missed_keys)),
+    ];
+    if let Some(seed) = summary.seed {
+        lines.push(Spans::from(format!(
+            "Seed:         {} (s in the lesson selection types its text)",
+            seed
+        )));
+    }
+    for achievement in summary.new_achievements.iter() {
+        lines.push(Spans::from(Span::styled(
+            format!(
+                "Achievement unlocked: {}",
+                describe_achievement(achievement)
+            ),
+            style.add_modifier(Modifier::BOLD),
+        )));
+    }
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        "r: retry   t: same text   v: replay   Enter: continue   Esc: back to lessons",
+        theme.inactive_style(),
+    )));
+    let summary_widget = Paragraph::new(lines)
+        .block(Block::default().title("Results").borders(Borders::ALL))
+        .style(style);
+    f.render_widget(summary_widget, area);
+}
+
+fn draw_routine_summary<B: Backend>(
+    f: &mut Frame<B>,
+    summary: &RoutineSummary,
+    theme: &Theme,
+    area: Rect,
+) {
+    let style = theme.active_style();
+    let total_stats = summary.total_stats();
+    let mut lines = vec![
+        Spans::from(Span::styled(
+            format!("{} finished", summary.routine_name),
+            style.add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(""),
+    ];
+    let steps = summary
+        .steps
+        .iter()
+        .map(|step| (step.lesson_name.as_str(), &step.stats))
+        .chain(std::iter::once(("Total", &total_stats)));
+    for (name, stats) in steps {
+        lines.push(Spans::from(format!(
+            "{:<24}{:>4} WPM (net {:>3}){:>7.1} %{:>5}:{:02}",
+            name,
+            stats.typing_speed.words_per_minute(),
+            stats.net_words_per_minute(),
+            100.0 * stats.accuracy(),
+            stats.duration_seconds / 60,
+            stats.duration_seconds % 60
+        )));
+    }
+    for achievement in summary
+        .steps
+        .iter()
+        .flat_map(|step| step.new_achievements.iter())
+    {
+        lines.push(Spans::from(Span::styled(
+            format!(
+                "Achievement unlocked: {}",
+                describe_achievement(achievement)
+            ),
+            style.add_modifier(Modifier::BOLD),
+        )));
+    }
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(Span::styled(
+        "r: repeat routine   Enter: continue   Esc: back to lessons",
+        theme.inactive_style(),
+    )));
+    let summary_widget = Paragraph::new(lines)
+        .block(Block::default().title("Results").borders(Borders::ALL))
+        .style(style);
+    f.render_widget(summary_widget, area);
 }
 
-fn draw_training<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, area: Rect) {
-    let style = get_style_depending_on_app_state(app.state(), AppState::Training);
+fn draw_training<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme, area: Rect) {
+    let style = training_style(theme, app);
+    let mut title = String::from("Training");
+    if let (AppState::Replay, Some(replay)) = (app.state(), app.replay()) {
+        title = format!(
+            "Replay x{} (+/- to change the speed, Esc to leave)",
+            replay.speed()
+        );
+        if let Some(hesitation) = replay.hesitation() {
+            title = format!(
+                "{} - hesitating {:.1} s",
+                title,
+                hesitation.num_milliseconds() as f64 / 1000.0
+            );
+        } else if replay.is_finished() {
+            title.push_str(" - finished");
+        }
+    } else if let Some((routine_name, step, step_count)) = app.routine_progress() {
+        title = format!("{} - {} step {}/{}", title, routine_name, step, step_count);
+    }
+    if app.state() == &AppState::Training && app.lesson_progress().is_paused() {
+        title.push_str(" (paused, press any key to resume)");
+    } else if app.state() != &AppState::Training && app.has_suspended_session() {
+        title.push_str(" (paused, press r in the lesson selection to resume)");
+    }
+    let session = app.lesson_progress();
+    let today = Clock::now().naive_utc().date();
+    if session.seed() == Some(lesson_of_the_day_seed(today)) {
+        title.push_str(" - lesson of the day");
+    }
+    match (session.ghost_lead(), session.ghost_speed()) {
+        (Some(lead), Some(speed)) if app.state() == &AppState::Training && session.is_started() => {
+            title = format!("{} - {}", title, describe_lead(lead, speed));
+        }
+        _ if app.state() != &AppState::Replay && app.lesson_list.ghost().is_some() => {
+            title = format!(
+                "{} - ghost: {}",
+                title,
+                Ghost::describe(app.lesson_list.ghost())
+            );
+        }
+        _ => {}
+    }
     let block = Block::default()
         .borders(Borders::ALL)
         .style(style)
-        .title("Training");
+        .title(title);
     f.render_widget(block, area);
 
+    let mut constraints = vec![
+        Constraint::Length(3),
+        Constraint::Length(7),
+        Constraint::Min(9),
+    ];
+    if app.show_keyboard() {
+        constraints[2] = Constraint::Min(2);
+        constraints.push(Constraint::Length(KEYBOARD_HEIGHT));
+    }
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(2)
-        .constraints(
-            [
-                Constraint::Length(3),
-                Constraint::Length(4),
-                Constraint::Min(9),
-            ]
-            .as_ref(),
-        )
+        .constraints(constraints)
         .split(area);
 
     let progress_widget = Gauge::default()
@@ -93,13 +776,165 @@ fn draw_training<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, area: Rect) {
     .widths(&[Constraint::Length(5), Constraint::Length(5)])
     .block(Block::default().title("Table").borders(Borders::ALL))
     .style(style);
-    f.render_widget(current_training_statistics_widget, chunks[1]);
+    let statistics_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(14), Constraint::Min(30)].as_ref())
+        .split(chunks[1]);
+    f.render_widget(current_training_statistics_widget, statistics_chunks[0]);
+
+    let mut layer_rows = vec![Row::new(vec!["Layer", "Typed", "Key", "Modifier"])];
+    for (layer, layer_stats) in training_stats.layers.iter() {
+        layer_rows.push(Row::new(vec![
+            layer.name().to_string(),
+            layer_stats.typed_characters.to_string(),
+            layer_stats.base_key_errors.to_string(),
+            layer_stats.modifier_errors.to_string(),
+        ]));
+    }
+    let layer_statistics_widget = Table::new(layer_rows)
+        .widths(&[
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(4),
+            Constraint::Length(8),
+        ])
+        .block(Block::default().title("Layers").borders(Borders::ALL))
+        .style(style);
+    f.render_widget(layer_statistics_widget, statistics_chunks[1]);
 
-    let lesson_text_widget = Paragraph::new(app.lesson_progress().get_diff());
+    let lesson_text_widget =
+        Paragraph::new(render_segments(&app.lesson_progress().segments(), theme));
     f.render_widget(lesson_text_widget, chunks[2]);
+
+    if app.show_keyboard() {
+        draw_keyboard(f, app, theme, chunks[3]);
+    }
+}
+
+const KEYBOARD_HEIGHT: u16 = 7;
+// indentation of the rows of a row-staggered keyboard, in characters
+const KEYBOARD_ROW_INDENT: [usize; 4] = [0, 6, 7, 9];
+
+fn render_segments<'a>(segments: &[Segment], theme: &Theme) -> Text<'a> {
+    // keeps the current position visible once everything was typed
+    const ZERO_WIDTH_CHAR: &str = "\u{200B}";
+    let spans: Vec<Span> = segments
+        .iter()
+        .map(|segment| {
+            let style = match segment.state {
+                SegmentState::Done => theme.typed_style(),
+                SegmentState::Current => theme.current_style(),
+                SegmentState::Wrong => theme.wrong_style(),
+                SegmentState::Pending => theme.pending_style(),
+                SegmentState::Ghost => theme.ghost_style(),
+            };
+            let text = match segment.state {
+                SegmentState::Current | SegmentState::Wrong if segment.text.is_empty() => {
+                    String::from(ZERO_WIDTH_CHAR)
+                }
+                _ => segment.text.clone(),
+            };
+            Span::styled(text, style)
+        })
+        .collect();
+    Text::from(Spans::from(spans))
 }
-fn draw_statistics<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, area: Rect) {
-    let block = Block::default().title("Statistics").borders(Borders::ALL);
+
+fn draw_keyboard<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme, area: Rect) {
+    let style = training_style(theme, app);
+    let layout = match app.lesson_list.layout() {
+        Some(layout) => layout,
+        None => {
+            let hint = Paragraph::new("No keyboard layout configured").style(style);
+            f.render_widget(hint, area);
+            return;
+        }
+    };
+    let session = app.lesson_progress();
+    let next_key = session.current_char().and_then(|c| layout.find(c));
+    let wrong_key = session
+        .last_wrong_input()
+        .and_then(|c| layout.find(&c.to_string()));
+
+    let hint = match (session.current_char(), next_key) {
+        (Some(next_char), Some((position, layer))) => {
+            let modifier = match layer {
+                Layer::Base => String::new(),
+                layer => format!("{} + ", layer.name()),
+            };
+            format!(
+                "next: {}  {}{}",
+                next_char,
+                modifier,
+                position.finger().name()
+            )
+        }
+        (Some(next_char), None) => format!("next: {}", next_char),
+        (None, _) => String::new(),
+    };
+    let displayed_layer = next_key.map(|(_, layer)| layer).unwrap_or(Layer::Base);
+    let next_key = next_key.map(|(position, _)| position);
+    let wrong_key = wrong_key.map(|(position, _)| position);
+    let mut lines = render_keyboard_rows(layout, displayed_layer, |position, _| {
+        if Some(position) == wrong_key {
+            theme.wrong_key_style()
+        } else if Some(position) == next_key {
+            theme.next_key_style()
+        } else {
+            style
+        }
+    });
+    let required_width = lines.iter().map(Spans::width).max().unwrap_or(0) as u16 + 2;
+    let required_height = lines.len() as u16 + 3;
+    if area.width < required_width || area.height < required_height {
+        // not enough room for the keyboard, the hint alone still helps
+        f.render_widget(Paragraph::new(hint).style(style), area);
+        return;
+    }
+    lines.push(Spans::from(Span::styled(hint, style)));
+    let keyboard_widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(layout.name.as_str())
+            .borders(Borders::ALL)
+            .style(style),
+    );
+    f.render_widget(keyboard_widget, area);
+}
+
+/// The keys of `layer`, or of the base layer where `layer` has none,
+/// styled by their position and label.
+fn render_keyboard_rows<'a>(
+    layout: &KeyboardLayout,
+    layer: Layer,
+    key_style: impl Fn(KeyPosition, char) -> Style,
+) -> Vec<Spans<'a>> {
+    (0..layout.row_count() as u8)
+        .map(|row| {
+            let indent = KEYBOARD_ROW_INDENT
+                .get(row as usize)
+                .copied()
+                .unwrap_or_default();
+            let mut spans = vec![Span::raw(" ".repeat(indent))];
+            for key in layout.row(row) {
+                let label = key
+                    .character(layer)
+                    .or_else(|| key.character(Layer::Base))
+                    .unwrap_or(' ');
+                spans.push(Span::styled(
+                    format!(" {} ", label),
+                    key_style(key.position, label),
+                ));
+                spans.push(Span::raw(" "));
+            }
+            Spans::from(spans)
+        })
+        .collect()
+}
+fn draw_statistics<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, theme: &Theme, area: Rect) {
+    let block = Block::default()
+        .title("Statistics")
+        .borders(Borders::ALL)
+        .style(theme.inactive_style());
     f.render_widget(block, area);
 
     let chunks = Layout::default()
@@ -108,16 +943,17 @@ fn draw_statistics<B: Backend>(f: &mut Frame<B>, app: &TrainerApp, area: Rect) {
         .margin(1)
         .split(area);
     let current_training_records = app.lesson_list.current_lesson_records();
-    render_wpm_chart(f, current_training_records, chunks[0]);
-    render_error_chart(f, current_training_records, chunks[1]);
+    render_wpm_chart(f, current_training_records, theme, chunks[0]);
+    render_error_chart(f, current_training_records, theme, chunks[1]);
 }
 
 fn render_wpm_chart<B: Backend>(
     f: &mut Frame<B>,
     current_training_records: &[TrainingRecord],
+    theme: &Theme,
     area: Rect,
 ) {
-    let style = get_inactive_style();
+    let style = theme.inactive_style();
     let wpm_data =
         c![r.stats.typing_speed.words_per_minute(), for r in current_training_records.iter()];
     let axis_max_wpm = match wpm_data.iter().max() {
@@ -155,9 +991,10 @@ fn render_wpm_chart<B: Backend>(
 fn render_error_chart<B: Backend>(
     f: &mut Frame<B>,
     current_training_records: &[TrainingRecord],
+    theme: &Theme,
     area: Rect,
 ) {
-    let style = get_inactive_style();
+    let style = theme.inactive_style();
     let error_data = c![r.stats.errors.total_error_count, for r in current_training_records.iter()];
     let axis_max_error = match error_data.iter().max() {
         None => 20,
@@ -190,18 +1027,23 @@ fn render_error_chart<B: Backend>(
     f.render_widget(error_chart, area);
 }
 
-fn get_style_depending_on_app_state(current_state: &AppState, active_state: AppState) -> Style {
-    if current_state == &active_state {
-        get_active_style()
+/// The training panel is also active while a replay is shown in it.
+fn training_style(theme: &Theme, app: &TrainerApp) -> Style {
+    if app.state() == &AppState::Replay {
+        theme.active_style()
     } else {
-        get_inactive_style()
+        get_style_depending_on_app_state(theme, app.state(), AppState::Training)
     }
 }
 
-fn get_active_style() -> Style {
-    Style::default().fg(Color::LightCyan)
-}
-
-fn get_inactive_style() -> Style {
-    Style::default().fg(Color::DarkGray)
+fn get_style_depending_on_app_state(
+    theme: &Theme,
+    current_state: &AppState,
+    active_state: AppState,
+) -> Style {
+    if current_state == &active_state {
+        theme.active_style()
+    } else {
+        theme.inactive_style()
+    }
 }
@@ -0,0 +1,336 @@
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+
+use serde::{Deserialize, Serialize};
+use tui::style::{Color, Modifier, Style};
+
+/// Contents of the theme file, either the name of a built-in theme, e.g.
+/// `{"Builtin": "high-contrast"}`, or a complete theme of its own.
+#[derive(Serialize, Deserialize)]
+pub enum ThemeConfig {
+    Builtin(String),
+    Custom(Theme),
+}
+
+/// All colours used by the TUI. Colours are given as accepted by `tui`, e.g.
+/// `"LightCyan"`, `{"Indexed": 208}` or `{"Rgb": [230, 159, 0]}`.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct Theme {
+    /// Text of the focused panel.
+    pub active: Color,
+    /// Text of the other panels and of hints.
+    pub inactive: Color,
+    pub background: Color,
+    pub typed: Color,
+    pub current: Color,
+    pub wrong: Color,
+    pub pending: Color,
+    /// Text on highlighted keys of the keyboard.
+    pub key_label: Color,
+    pub next_key: Color,
+    pub wrong_key: Color,
+    pub goal_reached: Color,
+    pub goal_partial: Color,
+    /// Background of the grapheme the ghost is at.
+    #[serde(default = "default_ghost_color")]
+    pub ghost: Color,
+    /// Lessons that are not unlocked yet, shown dimmed.
+    #[serde(default = "default_locked_color")]
+    pub locked: Color,
+    /// The selected entry of a list, shown in bold.
+    #[serde(default = "default_highlight_color")]
+    pub highlight: Color,
+}
+
+fn default_ghost_color() -> Color {
+    Color::Magenta
+}
+
+fn default_locked_color() -> Color {
+    Color::DarkGray
+}
+
+fn default_highlight_color() -> Color {
+    Color::LightCyan
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            active: Color::LightCyan,
+            inactive: Color::DarkGray,
+            background: Color::Reset,
+            typed: Color::Green,
+            current: Color::Gray,
+            wrong: Color::Red,
+            pending: Color::DarkGray,
+            key_label: Color::Black,
+            next_key: Color::LightCyan,
+            wrong_key: Color::Red,
+            goal_reached: Color::Green,
+            goal_partial: Color::Yellow,
+            ghost: default_ghost_color(),
+            locked: default_locked_color(),
+            highlight: default_highlight_color(),
+        }
+    }
+}
+
+impl Theme {
+    pub const BUILTIN_NAMES: [&'static str; 3] = ["default", "high-contrast", "colour-blind"];
+
+    /// Bright colours on black.
+    pub fn high_contrast() -> Theme {
+        Theme {
+            active: Color::White,
+            inactive: Color::Gray,
+            background: Color::Black,
+            typed: Color::LightGreen,
+            current: Color::LightYellow,
+            wrong: Color::LightRed,
+            pending: Color::Gray,
+            key_label: Color::Black,
+            next_key: Color::LightYellow,
+            wrong_key: Color::LightRed,
+            goal_reached: Color::LightGreen,
+            goal_partial: Color::LightYellow,
+            ghost: Color::LightMagenta,
+            locked: Color::Gray,
+            highlight: Color::LightYellow,
+        }
+    }
+
+    /// Blue and orange from the Okabe-Ito palette instead of green and red.
+    pub fn colour_blind() -> Theme {
+        let blue = Color::Rgb(0, 114, 178);
+        let sky_blue = Color::Rgb(86, 180, 233);
+        let orange = Color::Rgb(230, 159, 0);
+        Theme {
+            active: sky_blue,
+            inactive: Color::DarkGray,
+            background: Color::Reset,
+            typed: blue,
+            current: Color::White,
+            wrong: orange,
+            pending: Color::DarkGray,
+            key_label: Color::Black,
+            next_key: sky_blue,
+            wrong_key: orange,
+            goal_reached: blue,
+            goal_partial: Color::Rgb(240, 228, 66),
+            ghost: Color::Rgb(204, 121, 167),
+            locked: Color::DarkGray,
+            highlight: sky_blue,
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Theme> {
+        match name {
+            "default" => Some(Theme::default()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "colour-blind" => Some(Theme::colour_blind()),
+            _ => None,
+        }
+    }
+
+    /// Reads the theme file, the default theme is used if there is none.
+    pub fn load(file_path: &str) -> anyhow::Result<Theme> {
+        let file = match OpenOptions::new().read(true).open(file_path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Theme::default()),
+            Err(error) => return Err(error.into()),
+        };
+        match serde_json::from_reader(file)? {
+            ThemeConfig::Builtin(name) => Theme::by_name(&name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "unknown theme {}, built-in themes are {}",
+                    name,
+                    Theme::BUILTIN_NAMES.join(", ")
+                )
+            }),
+            ThemeConfig::Custom(theme) => Ok(theme),
+        }
+    }
+
+    /// Replaces all colours by the closest of the 16 standard terminal
+    /// colours.
+    pub fn limited_to_16_colors(self) -> Theme {
+        Theme {
+            active: closest_standard_color(self.active),
+            inactive: closest_standard_color(self.inactive),
+            background: closest_standard_color(self.background),
+            typed: closest_standard_color(self.typed),
+            current: closest_standard_color(self.current),
+            wrong: closest_standard_color(self.wrong),
+            pending: closest_standard_color(self.pending),
+            key_label: closest_standard_color(self.key_label),
+            next_key: closest_standard_color(self.next_key),
+            wrong_key: closest_standard_color(self.wrong_key),
+            goal_reached: closest_standard_color(self.goal_reached),
+            goal_partial: closest_standard_color(self.goal_partial),
+            ghost: closest_standard_color(self.ghost),
+            locked: closest_standard_color(self.locked),
+            highlight: closest_standard_color(self.highlight),
+        }
+    }
+
+    /// Limits the colours unless the terminal announces support for more.
+    pub fn for_terminal(self) -> Theme {
+        if terminal_supports_more_than_16_colors() {
+            self
+        } else {
+            self.limited_to_16_colors()
+        }
+    }
+
+    fn text_style(&self, color: Color) -> Style {
+        Style::default().fg(color).bg(self.background)
+    }
+    pub fn active_style(&self) -> Style {
+        self.text_style(self.active)
+    }
+    pub fn inactive_style(&self) -> Style {
+        self.text_style(self.inactive)
+    }
+    pub fn typed_style(&self) -> Style {
+        self.text_style(self.typed)
+    }
+    pub fn current_style(&self) -> Style {
+        self.text_style(self.current)
+    }
+    pub fn wrong_style(&self) -> Style {
+        self.text_style(self.wrong)
+    }
+    pub fn pending_style(&self) -> Style {
+        self.text_style(self.pending)
+    }
+    pub fn next_key_style(&self) -> Style {
+        Style::default().fg(self.key_label).bg(self.next_key)
+    }
+    pub fn wrong_key_style(&self) -> Style {
+        Style::default().fg(self.key_label).bg(self.wrong_key)
+    }
+    pub fn ghost_style(&self) -> Style {
+        Style::default().fg(self.key_label).bg(self.ghost)
+    }
+    pub fn goal_reached_style(&self) -> Style {
+        self.text_style(self.goal_reached)
+    }
+    pub fn goal_partial_style(&self) -> Style {
+        self.text_style(self.goal_partial)
+    }
+    pub fn locked_style(&self) -> Style {
+        self.text_style(self.locked).add_modifier(Modifier::DIM)
+    }
+    pub fn highlight_style(&self) -> Style {
+        Style::default()
+            .fg(self.highlight)
+            .add_modifier(Modifier::BOLD)
+    }
+}
+
+fn terminal_supports_more_than_16_colors() -> bool {
+    let color_term = std::env::var("COLORTERM").unwrap_or_default();
+    let term = std::env::var("TERM").unwrap_or_default();
+    color_term == "truecolor" || color_term == "24bit" || term.contains("256color")
+}
+
+/// The 16 standard colours with their usual (xterm) values.
+const STANDARD_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+fn closest_standard_color(color: Color) -> Color {
+    let (red, green, blue) = match color {
+        Color::Rgb(red, green, blue) => (red, green, blue),
+        Color::Indexed(index) => indexed_to_rgb(index),
+        standard_color => return standard_color,
+    };
+    let distance = |(r, g, b): (u8, u8, u8)| {
+        let difference = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        difference(r, red) + difference(g, green) + difference(b, blue)
+    };
+    STANDARD_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map(|(standard_color, _)| *standard_color)
+        .unwrap()
+}
+
+/// RGB value of a colour of the 256 colour palette.
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match index {
+        0..=15 => STANDARD_COLORS[index as usize].1,
+        16..=231 => {
+            let cube_index = index - 16;
+            (
+                CUBE_LEVELS[(cube_index / 36) as usize],
+                CUBE_LEVELS[(cube_index / 6 % 6) as usize],
+                CUBE_LEVELS[(cube_index % 6) as usize],
+            )
+        }
+        _ => {
+            let gray = 8 + 10 * (index - 232);
+            (gray, gray, gray)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_theme {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn builtin_themes_are_found_by_name() {
+        for name in Theme::BUILTIN_NAMES.iter() {
+            assert!(Theme::by_name(name).is_some(), "{}", name)
+        }
+        assert_eq!(Theme::by_name("neon"), None)
+    }
+    #[test]
+    fn theme_file_names_a_builtin_or_defines_a_theme() {
+        let builtin: ThemeConfig = serde_json::from_str(r#"{"Builtin": "high-contrast"}"#).unwrap();
+        assert!(matches!(builtin, ThemeConfig::Builtin(name) if name == "high-contrast"));
+        let custom = serde_json::to_string(&ThemeConfig::Custom(Theme::colour_blind())).unwrap();
+        let parsed: ThemeConfig = serde_json::from_str(&custom).unwrap();
+        assert!(matches!(parsed, ThemeConfig::Custom(theme) if theme == Theme::colour_blind()))
+    }
+    #[test]
+    fn colours_added_later_have_defaults() {
+        let mut custom = serde_json::to_value(Theme::high_contrast()).unwrap();
+        for colour in ["ghost", "locked", "highlight"].iter() {
+            custom.as_object_mut().unwrap().remove(*colour);
+        }
+        let theme: Theme = serde_json::from_value(custom).unwrap();
+        assert_eq!(theme.ghost, Color::Magenta);
+        assert_eq!(theme.locked, Color::DarkGray);
+        assert_eq!(theme.highlight, Color::LightCyan)
+    }
+    #[test]
+    fn colours_are_replaced_by_the_closest_standard_colour() {
+        let theme = Theme::colour_blind().limited_to_16_colors();
+        assert_eq!(theme.wrong, Color::Yellow);
+        assert_eq!(theme.typed, Color::Cyan);
+        assert_eq!(closest_standard_color(Color::Indexed(196)), Color::LightRed);
+        assert_eq!(closest_standard_color(Color::Indexed(244)), Color::DarkGray);
+        assert_eq!(closest_standard_color(Color::Reset), Color::Reset)
+    }
+}